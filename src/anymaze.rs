@@ -0,0 +1,285 @@
+// A maze whose shape is picked at runtime. `GenericMaze<S>` fixes its shape
+// at compile time, which suits code written for one shape but not a command
+// line, a collection of mazes of mixed shapes or an API that hands "some
+// maze" around. `AnyMaze` wraps a maze of any registered shape and forwards
+// each operation to it.
+//
+// To add a shape, give `AnyMaze` a variant for it, add the variant to
+// `with_maze!` and `impl_from!`, and list the shape in `SHAPES`. The enum is
+// closed, so only this crate registers shapes; a `Shape` implemented in
+// another crate is used through `GenericMaze<S>` instead.
+use rand::Rng;
+use std::any::Any;
+use std::io;
+
+use crate::endpoints::{place_endpoints, place_longest_path, Endpoint};
+use crate::generators::Generator;
+use crate::genericmaze::{GenericMaze, MazeCell, Shape};
+use crate::mazefile::{load_saved, saved_grid_type, MazeFile};
+use crate::packed;
+use crate::render::{PdfPage, Plot, StlOptions, TextStyle, Theme};
+use crate::shapes::{HexShape, OctShape, PolarShape, RectShape, TriShape};
+use crate::stats::MazeStats;
+
+/// A maze of any of the registered shapes
+pub enum AnyMaze {
+    Rectangular(GenericMaze<RectShape>),
+    Triangular(GenericMaze<TriShape>),
+    Hexagonal(GenericMaze<HexShape>),
+    Octagonal(GenericMaze<OctShape>),
+    Polar(GenericMaze<PolarShape>),
+}
+
+/// Evaluate `$body` with `$maze` bound to the `GenericMaze` inside an `AnyMaze`
+macro_rules! with_maze {
+    ($any:expr, $maze:ident => $body:expr) => {
+        match $any {
+            AnyMaze::Rectangular($maze) => $body,
+            AnyMaze::Triangular($maze) => $body,
+            AnyMaze::Hexagonal($maze) => $body,
+            AnyMaze::Octagonal($maze) => $body,
+            AnyMaze::Polar($maze) => $body,
+        }
+    };
+}
+
+macro_rules! impl_from {
+    ($($variant:ident($shape:ty)),*) => {
+        $(impl From<GenericMaze<$shape>> for AnyMaze {
+            fn from(maze: GenericMaze<$shape>) -> Self {
+                AnyMaze::$variant(maze)
+            }
+        })*
+    };
+}
+
+impl_from!(
+    Rectangular(RectShape),
+    Triangular(TriShape),
+    Hexagonal(HexShape),
+    Octagonal(OctShape),
+    Polar(PolarShape)
+);
+
+type Loaded = io::Result<(AnyMaze, Option<Vec<usize>>)>;
+
+/// What `AnyMaze` needs to know about a shape to create or load it by name
+struct Registration {
+    name: fn() -> &'static str,
+    new: fn(usize, usize) -> AnyMaze,
+    load: fn(&[u8]) -> Loaded,
+}
+
+impl Registration {
+    const fn of<S: Shape>() -> Self
+    where
+        AnyMaze: From<GenericMaze<S>>,
+    {
+        Registration {
+            name: S::name,
+            new: |width, height| GenericMaze::<S>::new(width, height).into(),
+            load: |data| load_saved::<S>(data).map(|(maze, solution)| (maze.into(), solution)),
+        }
+    }
+}
+
+/// Every shape `AnyMaze` can hold, in the order they are listed to users
+const SHAPES: [Registration; 5] = [
+    Registration::of::<RectShape>(),
+    Registration::of::<TriShape>(),
+    Registration::of::<HexShape>(),
+    Registration::of::<OctShape>(),
+    Registration::of::<PolarShape>(),
+];
+
+fn registration(grid_type: &str) -> Option<&'static Registration> {
+    SHAPES.iter().find(|shape| (shape.name)() == grid_type)
+}
+
+fn shape_name<S: Shape>(_maze: &GenericMaze<S>) -> &'static str {
+    S::name()
+}
+
+impl AnyMaze {
+    /// Names of the registered shapes, as used on the command line and in saved mazes
+    pub fn grid_types() -> Vec<&'static str> {
+        SHAPES.iter().map(|shape| (shape.name)()).collect()
+    }
+
+    /// Create a maze of the named shape with every wall standing, or `None`
+    /// if no shape of that name is registered
+    pub fn new(grid_type: &str, width: usize, height: usize) -> Option<Self> {
+        registration(grid_type).map(|shape| (shape.new)(width, height))
+    }
+
+    /// Load a saved maze of any registered shape (see `mazefile::load_saved`),
+    /// along with its solution if one was stored
+    pub fn load(data: &[u8]) -> Loaded {
+        let name = saved_grid_type(data)?;
+        let shape = registration(&name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown grid type '{}'", name)))?;
+        (shape.load)(data)
+    }
+
+    /// The maze inside, if it has shape `S`
+    pub fn into_generic<S: Shape>(self) -> Option<GenericMaze<S>> {
+        with_maze!(self, maze => (Box::new(maze) as Box<dyn Any>).downcast().ok().map(|maze| *maze))
+    }
+
+    /// Name of the maze's shape
+    pub fn grid_type(&self) -> &'static str {
+        with_maze!(self, maze => shape_name(maze))
+    }
+
+    pub fn width(&self) -> usize {
+        with_maze!(self, maze => maze.width)
+    }
+
+    pub fn height(&self) -> usize {
+        with_maze!(self, maze => maze.height)
+    }
+
+    pub fn cells(&self) -> &[MazeCell] {
+        with_maze!(self, maze => &maze.cells)
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        with_maze!(self, maze => maze.seed)
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        with_maze!(self, maze => maze.seed = seed)
+    }
+
+    pub fn entrance(&self) -> usize {
+        with_maze!(self, maze => maze.entrance)
+    }
+
+    pub fn exit(&self) -> usize {
+        with_maze!(self, maze => maze.exit)
+    }
+
+    /// See `GenericMaze::generate_with_rng`
+    pub fn generate_with_rng<G: Generator, R: Rng + ?Sized>(&mut self, generator: &G, rng: &mut R) {
+        with_maze!(self, maze => maze.generate_with_rng(generator, rng))
+    }
+
+    /// See `GenericMaze::braid`
+    pub fn braid<R: Rng + ?Sized>(&mut self, ratio: f64, rng: &mut R) {
+        with_maze!(self, maze => maze.braid(ratio, rng))
+    }
+
+    /// See `endpoints::place_endpoints`
+    pub fn place_endpoints(&mut self, entrance: Option<Endpoint>, exit: Option<Endpoint>) -> Result<(), String> {
+        with_maze!(self, maze => place_endpoints(maze, entrance, exit))
+    }
+
+    /// See `endpoints::place_longest_path`
+    pub fn place_longest_path(&mut self) -> Result<(), String> {
+        with_maze!(self, maze => place_longest_path(maze))
+    }
+
+    /// See `GenericMaze::solve`
+    pub fn solve(&self) -> Vec<usize> {
+        with_maze!(self, maze => maze.solve())
+    }
+
+    pub fn stats(&self, solution: &[usize]) -> MazeStats {
+        with_maze!(self, maze => MazeStats::compute(maze, solution))
+    }
+
+    pub fn print_debug_info(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        with_maze!(self, maze => Shape::print_debug_info(maze, out))
+    }
+
+    pub fn to_svg(&self, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, theme: &Theme) -> String {
+        with_maze!(self, maze => Shape::to_svg(maze, tunnel_width, solution_path, debug, theme))
+    }
+
+    pub fn to_png(&self, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, theme: &Theme, dpi: f64) -> io::Result<Vec<u8>> {
+        with_maze!(self, maze => Shape::to_png(maze, tunnel_width, solution_path, debug, theme, dpi))
+    }
+
+    pub fn pdf_page(&self, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, theme: &Theme) -> PdfPage {
+        with_maze!(self, maze => Shape::pdf_page(maze, tunnel_width, solution_path, debug, theme))
+    }
+
+    pub fn to_plot(&self, tunnel_width: usize, solution_path: Option<&[usize]>) -> Plot {
+        with_maze!(self, maze => Shape::to_plot(maze, tunnel_width, solution_path))
+    }
+
+    pub fn to_dxf(&self, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, dpi: f64) -> String {
+        with_maze!(self, maze => Shape::to_dxf(maze, tunnel_width, solution_path, debug, dpi))
+    }
+
+    pub fn to_stl(&self, tunnel_width: usize, solution_path: Option<&[usize]>, options: StlOptions) -> Vec<u8> {
+        with_maze!(self, maze => Shape::to_stl(maze, tunnel_width, solution_path, options))
+    }
+
+    pub fn to_text(&self, solution_path: Option<&[usize]>, style: TextStyle) -> String {
+        with_maze!(self, maze => Shape::to_text(maze, solution_path, style))
+    }
+
+    /// The maze (and optionally its solution) in the JSON save format
+    pub fn to_file(&self, solution: Option<&[usize]>) -> MazeFile {
+        with_maze!(self, maze => MazeFile::from_maze(maze, solution))
+    }
+
+    /// The maze in the compact binary save format
+    pub fn to_packed(&self) -> io::Result<Vec<u8>> {
+        with_maze!(self, maze => packed::to_packed(maze))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::Kruskal;
+    use crate::genericmaze::seeded_rng;
+
+    #[test]
+    fn test_any_maze() {
+        assert_eq!(AnyMaze::grid_types(), ["rectangular", "triangular", "hexagonal", "octagonal", "polar"]);
+        assert!(AnyMaze::new("spherical", 5, 5).is_none());
+
+        // Mazes of every shape side by side, picked by name
+        let mut mazes: Vec<AnyMaze> =
+            AnyMaze::grid_types().into_iter().map(|name| AnyMaze::new(name, 6, 5).unwrap()).collect();
+        for (maze, name) in mazes.iter_mut().zip(AnyMaze::grid_types()) {
+            assert_eq!(maze.grid_type(), name);
+            maze.generate_with_rng(&Kruskal, &mut seeded_rng(3));
+            maze.set_seed(Some(3));
+            maze.place_longest_path().unwrap();
+
+            let solution = maze.solve();
+            assert_eq!(solution.first(), Some(&maze.entrance()));
+            assert_eq!(maze.stats(&solution).passages, maze.cells().len() - 1);
+            assert!(maze.to_svg(20, Some(&solution), false, &Theme::default()).contains("<metadata>seed=3</metadata>"));
+
+            // The debug dump goes to the writer it is given
+            let mut dump = Vec::new();
+            maze.print_debug_info(&mut dump).unwrap();
+            let dump = String::from_utf8(dump).unwrap();
+            assert!(dump.contains(&format!("=== Maze Debug Info ({} grid) ===", name)));
+            assert_eq!(dump.matches("\nCell ").count(), maze.cells().len());
+
+            // Saved mazes come back as the same shape, in either format
+            for data in [maze.to_file(Some(&solution)).to_json().into_bytes(), maze.to_packed().unwrap()] {
+                let (loaded, _) = AnyMaze::load(&data).unwrap();
+                assert_eq!(loaded.grid_type(), name);
+                assert_eq!((loaded.entrance(), loaded.exit()), (maze.entrance(), maze.exit()));
+                assert!(loaded.cells().iter().zip(maze.cells()).all(|(a, b)| a.walls == b.walls));
+            }
+        }
+
+        // The concrete maze can be taken back out, but only as its own shape
+        let hex = mazes.remove(2);
+        let cells = hex.cells().len();
+        assert!(AnyMaze::new("hexagonal", 2, 2).unwrap().into_generic::<RectShape>().is_none());
+        assert_eq!(hex.into_generic::<HexShape>().unwrap().cells.len(), cells);
+
+        let mut unknown = MazeFile::from_maze(&GenericMaze::<RectShape>::new(2, 2), None);
+        unknown.grid_type = "spherical".to_string();
+        assert!(AnyMaze::load(unknown.to_json().as_bytes()).is_err());
+    }
+}
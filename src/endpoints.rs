@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+use crate::genericmaze::{GenericMaze, Shape};
+
+/// A side of the grid's bounding rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    North,
+    South,
+    East,
+    West,
+}
+
+/// Where to put the entrance or exit of a maze
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endpoint {
+    /// A specific cell, by (x, y) grid coordinates; must lie on the boundary
+    Cell(usize, usize),
+    /// The boundary cell closest to the middle of a side
+    Side(Side),
+    /// The boundary cell farthest (by solution length) from the other endpoint
+    Farthest,
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+
+    /// Parse `X,Y`, a side (`north`/`top`, `south`/`bottom`, `east`/`right`,
+    /// `west`/`left`) or `farthest`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "north" | "top" => Ok(Endpoint::Side(Side::North)),
+            "south" | "bottom" => Ok(Endpoint::Side(Side::South)),
+            "east" | "right" => Ok(Endpoint::Side(Side::East)),
+            "west" | "left" => Ok(Endpoint::Side(Side::West)),
+            "farthest" => Ok(Endpoint::Farthest),
+            other => {
+                let (x, y) = other
+                    .split_once(',')
+                    .ok_or_else(|| format!("'{}' is not X,Y, a side (north/south/east/west) or 'farthest'", s))?;
+                let x = x.trim().parse().map_err(|_| format!("invalid x coordinate in '{}'", s))?;
+                let y = y.trim().parse().map_err(|_| format!("invalid y coordinate in '{}'", s))?;
+                Ok(Endpoint::Cell(x, y))
+            }
+        }
+    }
+}
+
+/// Set the maze's entrance and exit. `None` keeps the current cell. The maze
+/// should already be generated, since `Farthest` measures distances through it.
+pub fn place_endpoints<S: Shape>(
+    maze: &mut GenericMaze<S>,
+    entrance: Option<Endpoint>,
+    exit: Option<Endpoint>,
+) -> Result<(), String> {
+    if entrance == Some(Endpoint::Farthest) && exit == Some(Endpoint::Farthest) {
+        return Err("entrance and exit cannot both be 'farthest'".to_string());
+    }
+
+    // Moved endpoints open on the new cell's preferred boundary wall
+    if entrance.is_some() {
+        maze.entrance_wall = None;
+    }
+    if exit.is_some() {
+        maze.exit_wall = None;
+    }
+
+    // Resolve fixed endpoints first so 'farthest' can measure from them
+    if let Some(endpoint) = entrance.filter(|e| *e != Endpoint::Farthest) {
+        maze.entrance = resolve(maze, endpoint)?;
+    }
+    if let Some(endpoint) = exit.filter(|e| *e != Endpoint::Farthest) {
+        maze.exit = resolve(maze, endpoint)?;
+    }
+    if entrance == Some(Endpoint::Farthest) {
+        maze.entrance = farthest_boundary_cell(maze, maze.exit);
+    }
+    if exit == Some(Endpoint::Farthest) {
+        maze.exit = farthest_boundary_cell(maze, maze.entrance);
+    }
+
+    // A one-cell maze can only be entered and left through the same cell
+    if maze.entrance == maze.exit && maze.cells.len() > 1 {
+        return Err("entrance and exit must be different cells".to_string());
+    }
+    Ok(())
+}
+
+/// The pair of boundary cells with the longest path between them, found with
+/// two BFS passes: the boundary cell farthest from an arbitrary boundary cell
+/// is one end, and the boundary cell farthest from that is the other. This is
+/// exact for perfect mazes (trees); on braided mazes it is a good heuristic.
+pub fn longest_boundary_path<S: Shape>(maze: &GenericMaze<S>) -> Option<(usize, usize)> {
+    let start = (0..maze.cells.len()).find(|&idx| is_boundary_cell(maze, idx))?;
+    let a = farthest_boundary_cell(maze, start);
+    let b = farthest_boundary_cell(maze, a);
+    (a != b).then_some((a, b))
+}
+
+/// Put the entrance and exit at the ends of the longest boundary-to-boundary
+/// path, for a guaranteed-long solution
+pub fn place_longest_path<S: Shape>(maze: &mut GenericMaze<S>) -> Result<(), String> {
+    let (entrance, exit) = longest_boundary_path(maze).ok_or("maze has fewer than two boundary cells")?;
+    maze.entrance = entrance;
+    maze.exit = exit;
+    maze.entrance_wall = None;
+    maze.exit_wall = None;
+    Ok(())
+}
+
+/// True if the cell has a boundary wall that can be opened
+pub fn is_boundary_cell<S: Shape>(maze: &GenericMaze<S>, idx: usize) -> bool {
+    maze.opening_wall(idx).is_some()
+}
+
+fn resolve<S: Shape>(maze: &GenericMaze<S>, endpoint: Endpoint) -> Result<usize, String> {
+    match endpoint {
+        Endpoint::Cell(x, y) => {
+            if y >= maze.height || x >= maze.row_len(y) {
+                return Err(format!("cell ({}, {}) is outside the {}x{} grid", x, y, maze.width, maze.height));
+            }
+            let idx = maze.cell_index(x, y);
+            if !is_boundary_cell(maze, idx) {
+                return Err(format!("cell ({}, {}) is not on the boundary", x, y));
+            }
+            Ok(idx)
+        }
+        Endpoint::Side(side) => side_cell(maze, side),
+        Endpoint::Farthest => unreachable!("farthest is resolved against the other endpoint"),
+    }
+}
+
+/// The boundary cell on a side that is closest to the middle of that side
+fn side_cell<S: Shape>(maze: &GenericMaze<S>, side: Side) -> Result<usize, String> {
+    let h = maze.height;
+    let cells: Vec<(usize, usize)> = match side {
+        Side::North => (0..maze.row_len(0)).map(|x| (x, 0)).collect(),
+        Side::South => (0..maze.row_len(h - 1)).map(|x| (x, h - 1)).collect(),
+        Side::West => (0..h).map(|y| (0, y)).collect(),
+        Side::East => (0..h).map(|y| (maze.row_len(y) - 1, y)).collect(),
+    };
+    let middle = cells.len() - 1;
+
+    cells
+        .iter()
+        .enumerate()
+        .map(|(pos, &(x, y))| (pos, maze.cell_index(x, y)))
+        .filter(|&(_, idx)| is_boundary_cell(maze, idx))
+        .min_by_key(|&(pos, _)| (2 * pos).abs_diff(middle))
+        .map(|(_, idx)| idx)
+        .ok_or_else(|| format!("no cell on the {:?} side can hold an opening", side))
+}
+
+/// The boundary cell with the longest path from `from`; ties go to the lowest index
+pub fn farthest_boundary_cell<S: Shape>(maze: &GenericMaze<S>, from: usize) -> usize {
+    let distances = maze.distances_from(from);
+    (0..maze.cells.len())
+        .filter(|&idx| idx != from && is_boundary_cell(maze, idx))
+        .filter_map(|idx| distances[idx].map(|d| (d, idx)))
+        .max_by_key(|&(d, idx)| (d, std::cmp::Reverse(idx)))
+        .map(|(_, idx)| idx)
+        .unwrap_or(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::GrowingTree;
+    use crate::genericmaze::seeded_rng;
+    use crate::render::Theme;
+    use crate::shapes::{HexShape, OctShape, RectShape, TriShape};
+    use crate::testutil::{build, options, shape_tests};
+
+    #[test]
+    fn test_custom_endpoints() {
+        fn check<S: Shape>(entrance: &str, exit: &str) {
+            let mut maze = build::<S>(&options::<S>(10, 8, 5));
+            place_endpoints(&mut maze, Some(entrance.parse().unwrap()), Some(exit.parse().unwrap())).unwrap();
+
+            let path = maze.solve();
+            assert_eq!(path.first(), Some(&maze.entrance));
+            assert_eq!(path.last(), Some(&maze.exit));
+
+            // Each endpoint leaves exactly one wall of the outline undrawn
+            let lines = |entrance: usize, exit: usize| {
+                let mut closed = GenericMaze::<S>::new(10, 8);
+                closed.entrance = entrance;
+                closed.exit = exit;
+                S::to_svg(&closed, 20, None, false, &Theme::default()).matches("<line").count()
+            };
+            let both = lines(maze.entrance, maze.exit);
+            assert_eq!(lines(maze.entrance, maze.entrance), both + 1);
+            assert_eq!(lines(maze.exit, maze.exit), both + 1);
+        }
+
+        check::<RectShape>("west", "east");
+        check::<RectShape>("0,3", "farthest");
+        check::<TriShape>("north", "south");
+        check::<HexShape>("farthest", "9,4");
+        check::<OctShape>("west", "farthest");
+
+        // A single cell is both entrance and exit; larger grids still need two
+        let mut single = GenericMaze::<RectShape>::new(1, 1);
+        assert!(place_endpoints(&mut single, None, None).is_ok());
+        assert_eq!(single.solve(), vec![0]);
+        let mut maze = GenericMaze::<RectShape>::new(2, 1);
+        assert!(place_endpoints(&mut maze, Some(Endpoint::Side(Side::West)), Some(Endpoint::Side(Side::West))).is_err());
+    }
+
+    fn test_longest_path_is_boundary_diameter<S: Shape>() {
+        let mut maze = GenericMaze::<S>::new(9, 7);
+        maze.generate_with_rng(&GrowingTree::for_difficulty(true), &mut seeded_rng(3));
+        place_longest_path(&mut maze).unwrap();
+        let found = maze.solve().len();
+
+        // Brute force over every pair of boundary cells
+        let boundary: Vec<usize> = (0..maze.cells.len()).filter(|&idx| maze.opening_wall(idx).is_some()).collect();
+        let best = boundary
+            .iter()
+            .map(|&a| {
+                let distances = maze.distances_from(a);
+                boundary.iter().map(|&b| distances[b].unwrap()).max().unwrap()
+            })
+            .max()
+            .unwrap();
+        assert_eq!(found, best + 1);
+    }
+
+    shape_tests!(test_longest_path_is_boundary_diameter);
+}
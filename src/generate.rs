@@ -0,0 +1,202 @@
+// One-call maze generation, as the command line does it: create a maze of a
+// shape picked by name, carve it from a seeded random stream, place the
+// entrance and exit, and regenerate until the difficulty constraints are met.
+use std::fmt;
+
+use crate::anymaze::AnyMaze;
+use crate::endpoints::Endpoint;
+use crate::generators::{AldousBroder, GrowingTree, HuntAndKill, Kruskal, Prim, Wilson};
+use crate::genericmaze::{seeded_rng, MazeRng};
+use crate::stats::Constraints;
+
+/// Which generator carves the maze
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    GrowingTree(GrowingTree),
+    Kruskal,
+    Prim,
+    Wilson,
+    AldousBroder,
+    HuntAndKill,
+}
+
+/// Everything that decides which maze `generate` produces
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateOptions {
+    /// Name of a registered shape (see `AnyMaze::grid_types`)
+    pub grid_type: String,
+    pub width: usize,
+    pub height: usize,
+    pub algorithm: Algorithm,
+    /// Seed for the random stream; `None` picks one at random
+    pub seed: Option<u64>,
+    /// Fraction of dead ends to remove by adding loops (see `GenericMaze::braid`)
+    pub braid: f64,
+    /// Where to put the entrance; `None` keeps the first cell
+    pub entrance: Option<Endpoint>,
+    /// Where to put the exit; `None` keeps the last cell
+    pub exit: Option<Endpoint>,
+    /// Put the entrance and exit at the ends of the longest boundary path instead
+    pub longest_path: bool,
+    pub constraints: Constraints,
+    /// Attempts at meeting the constraints before giving up (at least one is made)
+    pub max_attempts: usize,
+}
+
+impl GenerateOptions {
+    /// An easy growing-tree maze of the named shape, with a random seed, the
+    /// default endpoints and no constraints
+    pub fn new(grid_type: &str, width: usize, height: usize) -> Self {
+        GenerateOptions {
+            grid_type: grid_type.to_string(),
+            width,
+            height,
+            algorithm: Algorithm::GrowingTree(GrowingTree::for_difficulty(false)),
+            seed: None,
+            braid: 0.0,
+            entrance: None,
+            exit: None,
+            longest_path: false,
+            constraints: Constraints::default(),
+            max_attempts: 1000,
+        }
+    }
+}
+
+/// Why `generate` could not produce a maze
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenerateError {
+    UnknownGridType(String),
+    EmptyGrid,
+    /// The entrance or exit could not be placed as asked
+    Endpoints(String),
+    /// The constraints contradict each other, so no maze could meet them
+    InvalidConstraints(String),
+    ConstraintsNotMet { attempts: usize },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerateError::UnknownGridType(name) => write!(f, "unknown grid type '{}'", name),
+            GenerateError::EmptyGrid => write!(f, "width and height must be greater than 0"),
+            GenerateError::Endpoints(msg) => write!(f, "{}", msg),
+            GenerateError::InvalidConstraints(msg) => write!(f, "{}", msg),
+            GenerateError::ConstraintsNotMet { attempts } => {
+                write!(f, "no maze met the constraints after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// Generate the maze the options describe, recording its seed. Returns the
+/// maze and the number of attempts it took to meet the constraints.
+pub fn generate(options: &GenerateOptions) -> Result<(AnyMaze, usize), GenerateError> {
+    if options.width == 0 || options.height == 0 {
+        return Err(GenerateError::EmptyGrid);
+    }
+    options.constraints.validate().map_err(GenerateError::InvalidConstraints)?;
+    let seed = options.seed.unwrap_or_else(rand::random);
+
+    // Every attempt draws from the same seeded stream, so the accepted maze
+    // is still reproducible from the seed
+    let mut rng = seeded_rng(seed);
+
+    // A limit of zero still makes one attempt
+    let attempts = options.max_attempts.max(1);
+    for attempt in 1..=attempts {
+        let mut maze = AnyMaze::new(&options.grid_type, options.width, options.height)
+            .ok_or_else(|| GenerateError::UnknownGridType(options.grid_type.clone()))?;
+        carve(&mut maze, options, &mut rng);
+        maze.set_seed(Some(seed));
+
+        let placed = if options.longest_path {
+            maze.place_longest_path()
+        } else {
+            maze.place_endpoints(options.entrance, options.exit)
+        };
+        placed.map_err(GenerateError::Endpoints)?;
+
+        if options.constraints.is_empty() || options.constraints.satisfied_by(&maze.stats(&maze.solve())) {
+            return Ok((maze, attempt));
+        }
+    }
+
+    Err(GenerateError::ConstraintsNotMet { attempts })
+}
+
+fn carve(maze: &mut AnyMaze, options: &GenerateOptions, rng: &mut MazeRng) {
+    match options.algorithm {
+        Algorithm::GrowingTree(selection) => maze.generate_with_rng(&selection, rng),
+        Algorithm::Kruskal => maze.generate_with_rng(&Kruskal, rng),
+        Algorithm::Prim => maze.generate_with_rng(&Prim, rng),
+        Algorithm::Wilson => maze.generate_with_rng(&Wilson, rng),
+        Algorithm::AldousBroder => maze.generate_with_rng(&AldousBroder, rng),
+        Algorithm::HuntAndKill => maze.generate_with_rng(&HuntAndKill, rng),
+    }
+
+    if options.braid > 0.0 {
+        maze.braid(options.braid, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::RectShape;
+    use crate::stats::MazeStats;
+    use crate::testutil::{build, options};
+
+    #[test]
+    fn test_constraints_are_met() {
+        let mut options = options::<RectShape>(12, 12, 9);
+        options.algorithm = Algorithm::GrowingTree(GrowingTree::for_difficulty(true));
+        options.constraints = Constraints { min_solution_ratio: Some(0.2), max_dead_ends: Some(40), ..Constraints::default() };
+
+        let (maze, attempts) = generate(&options).unwrap();
+        let maze = maze.into_generic::<RectShape>().unwrap();
+        let stats = MazeStats::compute(&maze, &maze.solve());
+        assert!(stats.solution_ratio.unwrap() >= 0.2);
+        assert!(stats.dead_ends <= 40);
+        assert!(attempts >= 1);
+        assert_eq!(maze.seed, Some(9));
+
+        // Same seed, same accepted maze
+        let (again, again_attempts) = generate(&options).unwrap();
+        assert_eq!(attempts, again_attempts);
+        assert!(maze.cells.iter().zip(again.cells()).all(|(a, b)| a.walls == b.walls));
+
+        // A seed is picked and recorded when none is given
+        let random = build::<RectShape>(&GenerateOptions::new("rectangular", 4, 4));
+        assert!(random.seed.is_some());
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        let unknown = GenerateOptions::new("spherical", 5, 5);
+        assert_eq!(generate(&unknown).err(), Some(GenerateError::UnknownGridType("spherical".to_string())));
+        assert_eq!(generate(&GenerateOptions::new("hexagonal", 0, 5)).err(), Some(GenerateError::EmptyGrid));
+
+        let mut stuck = options::<RectShape>(2, 1, 1);
+        stuck.entrance = Some(Endpoint::Cell(5, 0));
+        assert!(matches!(generate(&stuck), Err(GenerateError::Endpoints(_))));
+
+        // A perfect maze always has dead ends
+        let mut impossible = options::<RectShape>(6, 6, 1);
+        impossible.constraints.max_dead_ends = Some(0);
+        impossible.max_attempts = 5;
+        assert_eq!(generate(&impossible).err(), Some(GenerateError::ConstraintsNotMet { attempts: 5 }));
+        impossible.max_attempts = 0;
+        assert_eq!(generate(&impossible).err(), Some(GenerateError::ConstraintsNotMet { attempts: 1 }));
+
+        // Contradictory bounds are rejected before any attempt is made
+        let mut contradictory = options::<RectShape>(6, 6, 1);
+        contradictory.constraints.min_dead_ends = Some(10);
+        contradictory.constraints.max_dead_ends = Some(2);
+        assert!(matches!(generate(&contradictory), Err(GenerateError::InvalidConstraints(_))));
+        contradictory.constraints = Constraints { min_solution_ratio: Some(0.5), max_solution_ratio: Some(0.2), ..Constraints::default() };
+        assert!(matches!(generate(&contradictory), Err(GenerateError::InvalidConstraints(_))));
+    }
+}
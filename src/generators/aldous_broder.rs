@@ -0,0 +1,34 @@
+use rand::Rng;
+
+use crate::genericmaze::{GenericMaze, Shape, SliceRandom};
+use super::Generator;
+
+/// Aldous-Broder: a pure random walk that carves into every cell the first
+/// time it is entered. Uniform like Wilson's, but slow to finish on big grids.
+pub struct AldousBroder;
+
+impl Generator for AldousBroder {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R) {
+        let num_cells = maze.cells.len();
+        if num_cells == 0 {
+            return;
+        }
+
+        let mut visited = vec![false; num_cells];
+        let mut current = rng.gen_range(0..num_cells);
+        visited[current] = true;
+        let mut remaining = num_cells - 1;
+
+        while remaining > 0 {
+            let neighbors: Vec<(usize, usize)> = maze.neighbors_of(current).collect();
+            let &(edge_idx, next) = neighbors.choose(rng).expect("cell has no neighbors");
+
+            if !visited[next] {
+                maze.carve(current, edge_idx);
+                visited[next] = true;
+                remaining -= 1;
+            }
+            current = next;
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use rand::Rng;
+use std::str::FromStr;
+
+use crate::genericmaze::{GenericMaze, Shape, SliceRandom};
+use super::Generator;
+
+/// Growing-tree generation: keep a list of active cells, repeatedly pick one,
+/// carve to a random unvisited neighbor, and retire the cell once it has none.
+///
+/// Which active cell gets picked is a weighted mix of three strategies:
+/// - newest: LIFO, gives long winding corridors (recursive backtracker)
+/// - random: gives more uniform branching (similar to simplified Prim's)
+/// - oldest: FIFO, gives long straight corridors radiating from the start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrowingTree {
+    pub newest: f64,
+    pub random: f64,
+    pub oldest: f64,
+}
+
+impl GrowingTree {
+    /// Easy = always newest (long corridors), hard = always random (more branching)
+    pub fn for_difficulty(is_hard: bool) -> Self {
+        if is_hard {
+            GrowingTree { newest: 0.0, random: 1.0, oldest: 0.0 }
+        } else {
+            GrowingTree { newest: 1.0, random: 0.0, oldest: 0.0 }
+        }
+    }
+
+    /// Pick the position of the next cell in the active list
+    fn pick<R: Rng + ?Sized>(&self, len: usize, rng: &mut R) -> usize {
+        let total = self.newest + self.random + self.oldest;
+        // Only spend a roll when strategies are actually mixed
+        let mixed = [self.newest, self.random, self.oldest].iter().filter(|&&w| w > 0.0).count() > 1;
+        let roll = if mixed { rng.gen_range(0.0..total) } else { 0.0 };
+
+        if roll < self.newest {
+            len - 1
+        } else if roll < self.newest + self.random {
+            rng.gen_range(0..len)
+        } else {
+            0
+        }
+    }
+}
+
+impl Generator for GrowingTree {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R) {
+        let mut visited = vec![false; maze.cells.len()];
+        let mut active = vec![0];
+        visited[0] = true;
+
+        // Order only matters for newest/oldest selection; pure random selection
+        // can use the O(1) swap_remove
+        let keep_order = self.newest > 0.0 || self.oldest > 0.0;
+
+        while !active.is_empty() {
+            let pos = self.pick(active.len(), rng);
+            let current = active[pos];
+
+            // Find unvisited neighbors
+            let unvisited: Vec<(usize, usize)> = maze
+                .neighbors_of(current)
+                .filter(|&(_, neighbor)| !visited[neighbor])
+                .collect();
+
+            if let Some(&(edge_idx, next)) = unvisited.choose(rng) {
+                maze.carve(current, edge_idx);
+                visited[next] = true;
+                active.push(next);
+            } else if keep_order {
+                active.remove(pos);
+            } else {
+                active.swap_remove(pos);
+            }
+        }
+    }
+}
+
+impl FromStr for GrowingTree {
+    type Err = String;
+
+    /// Parse a selection mix such as `newest=3,random=1` or just `oldest`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tree = GrowingTree { newest: 0.0, random: 0.0, oldest: 0.0 };
+
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, weight) = match part.split_once('=') {
+                Some((name, weight)) => {
+                    let weight: f64 = weight
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid weight in '{}'", part))?;
+                    (name.trim(), weight)
+                }
+                None => (part, 1.0),
+            };
+
+            if !(weight >= 0.0 && weight.is_finite()) {
+                return Err(format!("weight for '{}' must be a non-negative number", name));
+            }
+
+            match name {
+                "newest" => tree.newest = weight,
+                "random" => tree.random = weight,
+                "oldest" => tree.oldest = weight,
+                _ => return Err(format!("unknown selection '{}' (expected newest, random or oldest)", name)),
+            }
+        }
+
+        if tree.newest + tree.random + tree.oldest <= 0.0 {
+            return Err("at least one selection weight must be positive".to_string());
+        }
+
+        Ok(tree)
+    }
+}
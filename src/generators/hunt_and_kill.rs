@@ -0,0 +1,64 @@
+use rand::Rng;
+
+use crate::genericmaze::{GenericMaze, Shape, SliceRandom};
+use super::Generator;
+
+/// Hunt-and-kill: random walk into unvisited cells until stuck, then scan for
+/// the first unvisited cell bordering the visited region and resume from there
+pub struct HuntAndKill;
+
+impl Generator for HuntAndKill {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R) {
+        let num_cells = maze.cells.len();
+        if num_cells == 0 {
+            return;
+        }
+
+        let mut visited = vec![false; num_cells];
+        let mut current = rng.gen_range(0..num_cells);
+        visited[current] = true;
+
+        // Every cell before this index is known to be visited
+        let mut hunt_from = 0;
+
+        loop {
+            // Kill: walk to a random unvisited neighbor
+            let unvisited: Vec<(usize, usize)> = maze
+                .neighbors_of(current)
+                .filter(|&(_, neighbor)| !visited[neighbor])
+                .collect();
+
+            if let Some(&(edge_idx, next)) = unvisited.choose(rng) {
+                maze.carve(current, edge_idx);
+                visited[next] = true;
+                current = next;
+                continue;
+            }
+
+            // Hunt: find an unvisited cell with a visited neighbor and join it on
+            while hunt_from < num_cells && visited[hunt_from] {
+                hunt_from += 1;
+            }
+
+            let mut found = None;
+            for idx in hunt_from..num_cells {
+                if visited[idx] {
+                    continue;
+                }
+                let links: Vec<(usize, usize)> = maze
+                    .neighbors_of(idx)
+                    .filter(|&(_, neighbor)| visited[neighbor])
+                    .collect();
+                if let Some(&(edge_idx, _)) = links.choose(rng) {
+                    found = Some((idx, edge_idx));
+                    break;
+                }
+            }
+
+            let Some((idx, edge_idx)) = found else { break };
+            maze.carve(idx, edge_idx);
+            visited[idx] = true;
+            current = idx;
+        }
+    }
+}
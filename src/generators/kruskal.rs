@@ -0,0 +1,64 @@
+use rand::Rng;
+
+use crate::genericmaze::{GenericMaze, Shape, SliceRandom};
+use super::Generator;
+
+/// Randomized Kruskal's algorithm: visit every edge in random order and carve
+/// it whenever it joins two cells that are not yet connected
+pub struct Kruskal;
+
+/// Disjoint-set forest with path halving and union by size
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        DisjointSet { parent: (0..n).collect(), size: vec![1; n] }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merge the sets holding `a` and `b`; returns false if they were already joined
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+}
+
+impl Generator for Kruskal {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R) {
+        // Each shared edge once, from the lower-numbered cell
+        let mut edges: Vec<(usize, usize, usize)> = Vec::new();
+        for idx in 0..maze.cells.len() {
+            for (edge_idx, neighbor) in maze.neighbors_of(idx) {
+                if neighbor > idx {
+                    edges.push((idx, edge_idx, neighbor));
+                }
+            }
+        }
+        edges.shuffle(rng);
+
+        let mut sets = DisjointSet::new(maze.cells.len());
+        for (idx, edge_idx, neighbor) in edges {
+            if sets.union(idx, neighbor) {
+                maze.carve(idx, edge_idx);
+            }
+        }
+    }
+}
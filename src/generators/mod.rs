@@ -0,0 +1,92 @@
+// Maze generation algorithms, one per module. Every generator only walks
+// `MazeCell::neighbors`, so each one works unchanged on every `Shape`.
+mod growing_tree;
+mod kruskal;
+mod prim;
+mod wilson;
+mod aldous_broder;
+mod hunt_and_kill;
+
+pub use growing_tree::GrowingTree;
+pub use kruskal::Kruskal;
+pub use prim::Prim;
+pub use wilson::Wilson;
+pub use aldous_broder::AldousBroder;
+pub use hunt_and_kill::HuntAndKill;
+
+use rand::Rng;
+
+use crate::genericmaze::{GenericMaze, Shape};
+
+/// A maze generation algorithm
+///
+/// `generate` is handed a maze with every wall present and carves passages
+/// into it until every cell is reachable from every other.
+pub trait Generator {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::seeded_rng;
+    use crate::testutil::shape_tests;
+
+    /// A perfect maze is a spanning tree: exactly cells - 1 passages, all connected
+    fn assert_perfect<S: Shape, G: Generator>(generator: &G) {
+        let mut maze = GenericMaze::<S>::new(9, 7);
+        maze.generate_with_rng(generator, &mut seeded_rng(7));
+
+        let passages: usize = (0..maze.cells.len())
+            .map(|idx| maze.neighbors_of(idx).filter(|&(e, n)| n > idx && !maze.cells[idx].walls[e]).count())
+            .sum();
+        assert_eq!(passages, maze.cells.len() - 1);
+
+        let mut reached = vec![false; maze.cells.len()];
+        let mut stack = vec![0];
+        reached[0] = true;
+        while let Some(idx) = stack.pop() {
+            for (edge_idx, next) in maze.neighbors_of(idx) {
+                if !maze.cells[idx].walls[edge_idx] && !reached[next] {
+                    reached[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        assert!(reached.iter().all(|&r| r));
+    }
+
+    fn test_growing_tree_makes_perfect_mazes<S: Shape>() {
+        assert_perfect::<S, _>(&GrowingTree::for_difficulty(false));
+        assert_perfect::<S, _>(&GrowingTree::for_difficulty(true));
+    }
+
+    fn test_kruskal_makes_perfect_mazes<S: Shape>() {
+        assert_perfect::<S, _>(&Kruskal);
+    }
+
+    fn test_prim_makes_perfect_mazes<S: Shape>() {
+        assert_perfect::<S, _>(&Prim);
+    }
+
+    fn test_wilson_makes_perfect_mazes<S: Shape>() {
+        assert_perfect::<S, _>(&Wilson);
+    }
+
+    fn test_aldous_broder_makes_perfect_mazes<S: Shape>() {
+        assert_perfect::<S, _>(&AldousBroder);
+    }
+
+    fn test_hunt_and_kill_makes_perfect_mazes<S: Shape>() {
+        assert_perfect::<S, _>(&HuntAndKill);
+    }
+
+    shape_tests!(
+        test_growing_tree_makes_perfect_mazes,
+        test_kruskal_makes_perfect_mazes,
+        test_prim_makes_perfect_mazes,
+        test_wilson_makes_perfect_mazes,
+        test_aldous_broder_makes_perfect_mazes,
+        test_hunt_and_kill_makes_perfect_mazes,
+    );
+}
@@ -0,0 +1,59 @@
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::genericmaze::{GenericMaze, Shape};
+use super::Generator;
+
+/// True weighted Prim's algorithm: every edge gets a random weight and the
+/// maze is the minimum spanning tree grown from a random cell, always taking
+/// the cheapest edge leading out of the tree
+pub struct Prim;
+
+impl Generator for Prim {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R) {
+        let num_cells = maze.cells.len();
+        if num_cells == 0 {
+            return;
+        }
+
+        // Weight each edge once so both directions agree
+        let mut weights: Vec<Vec<u32>> = maze.cells.iter().map(|cell| vec![0; cell.neighbors.len()]).collect();
+        for idx in 0..num_cells {
+            for (edge_idx, neighbor) in maze.neighbors_of(idx).collect::<Vec<_>>() {
+                if neighbor > idx {
+                    let weight = rng.next_u32();
+                    weights[idx][edge_idx] = weight;
+                    for (rev_idx, &back) in maze.cells[neighbor].neighbors.iter().enumerate() {
+                        if back == Some(idx) {
+                            weights[neighbor][rev_idx] = weight;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut in_tree = vec![false; num_cells];
+        let mut heap = BinaryHeap::new();
+
+        let start = rng.gen_range(0..num_cells);
+        in_tree[start] = true;
+        for (edge_idx, neighbor) in maze.neighbors_of(start) {
+            heap.push(Reverse((weights[start][edge_idx], start, edge_idx, neighbor)));
+        }
+
+        while let Some(Reverse((_, idx, edge_idx, next))) = heap.pop() {
+            if in_tree[next] {
+                continue;
+            }
+
+            maze.carve(idx, edge_idx);
+            in_tree[next] = true;
+            for (next_edge, neighbor) in maze.neighbors_of(next) {
+                if !in_tree[neighbor] {
+                    heap.push(Reverse((weights[next][next_edge], next, next_edge, neighbor)));
+                }
+            }
+        }
+    }
+}
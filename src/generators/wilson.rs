@@ -0,0 +1,51 @@
+use rand::Rng;
+
+use crate::genericmaze::{GenericMaze, Shape, SliceRandom};
+use super::Generator;
+
+/// Wilson's algorithm: loop-erased random walks from each cell outside the
+/// tree until they hit it. Produces a uniformly random spanning tree.
+pub struct Wilson;
+
+impl Generator for Wilson {
+    fn generate<S: Shape, R: Rng + ?Sized>(&self, maze: &mut GenericMaze<S>, rng: &mut R) {
+        let num_cells = maze.cells.len();
+        if num_cells == 0 {
+            return;
+        }
+
+        let mut in_tree = vec![false; num_cells];
+        in_tree[rng.gen_range(0..num_cells)] = true;
+
+        // Edge taken when last leaving each cell during the current walk.
+        // Overwriting it on revisits is what erases loops.
+        let mut exit_edge = vec![0; num_cells];
+
+        let mut order: Vec<usize> = (0..num_cells).collect();
+        order.shuffle(rng);
+
+        for start in order {
+            if in_tree[start] {
+                continue;
+            }
+
+            // Random walk until the tree is reached
+            let mut current = start;
+            while !in_tree[current] {
+                let neighbors: Vec<(usize, usize)> = maze.neighbors_of(current).collect();
+                let &(edge_idx, next) = neighbors.choose(rng).expect("cell has no neighbors");
+                exit_edge[current] = edge_idx;
+                current = next;
+            }
+
+            // Retrace the loop-erased path, adding it to the tree
+            let mut current = start;
+            while !in_tree[current] {
+                let edge_idx = exit_edge[current];
+                in_tree[current] = true;
+                maze.carve(current, edge_idx);
+                current = maze.cells[current].neighbors[edge_idx].unwrap();
+            }
+        }
+    }
+}
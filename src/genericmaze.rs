@@ -1,18 +1,23 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::collections::{HashMap, VecDeque};
+use std::io;
 
-/// Helper functions for converting between (x, y) coordinates and cell indices
-pub fn cell_index(x: usize, y: usize, width: usize) -> usize {
-    y * width + x
-}
+use crate::generators::Generator;
+use crate::render::{
+    heat_color, Arc, CellFill, DxfPainter, Painter, PdfPage, PdfPainter, Plot, PlotPainter, Point, RasterPainter, Rgb, StlOptions,
+    StlPainter, SvgPainter, TextPainter, TextStyle, Theme, Underlay,
+};
 
-pub fn cell_coords(idx: usize, width: usize) -> (usize, usize) {
-    (idx % width, idx / width)
+/// Index of cell (x, y) in a grid whose rows are all `width` long
+pub(crate) fn cell_index(x: usize, y: usize, width: usize) -> usize {
+    y * width + x
 }
 
-trait SliceRandom {
+pub(crate) trait SliceRandom {
     type Item;
     fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&Self::Item>;
+    fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R);
 }
 
 impl<T> SliceRandom for [T] {
@@ -25,44 +30,321 @@ impl<T> SliceRandom for [T] {
             Some(&self[rng.gen_range(0..self.len())])
         }
     }
+
+    fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        // Fisher-Yates
+        for i in (1..self.len()).rev() {
+            self.swap(i, rng.gen_range(0..=i));
+        }
+    }
+}
+
+/// Deterministic RNG used for seeded generation. ChaCha8 is used rather than
+/// `StdRng` because its output is guaranteed stable across rand releases.
+pub type MazeRng = ChaCha8Rng;
+
+/// Create the RNG for a given seed
+pub fn seeded_rng(seed: u64) -> MazeRng {
+    MazeRng::seed_from_u64(seed)
 }
 
 /// A cell in the maze with neighbors and walls
 #[derive(Clone)]
 pub struct MazeCell {
-    pub neighbors: Vec<Option<usize>>,
-    pub walls: Vec<bool>,
+    pub(crate) neighbors: Vec<Option<usize>>,
+    pub(crate) walls: Vec<bool>,
 }
 
 impl MazeCell {
-    pub fn new(num_neighbors: usize) -> Self {
+    pub(crate) fn new(num_neighbors: usize) -> Self {
         MazeCell {
             neighbors: vec![None; num_neighbors],
             walls: vec![true; num_neighbors],
         }
     }
+
+    /// The neighboring cell across each edge, in the shape's neighbor order
+    pub fn neighbors(&self) -> &[Option<usize>] {
+        &self.neighbors
+    }
+
+    /// Whether each edge's wall is standing, in the shape's neighbor order
+    pub fn walls(&self) -> &[bool] {
+        &self.walls
+    }
+
+    /// Link the cell across `edge`, for `Shape::init_neighbors` in shapes
+    /// defined outside this crate
+    pub fn set_neighbor(&mut self, edge: usize, neighbor: Option<usize>) {
+        self.neighbors[edge] = neighbor;
+    }
 }
 
-/// Trait defining shape-specific behavior for different maze topologies
-pub trait Shape {
-    /// Number of neighbors for each cell in this shape
-    fn num_neighbors() -> usize;
+/// Trait defining shape-specific behavior for different maze topologies.
+/// Shapes are marker types, so they are `'static` and an `AnyMaze` can hand
+/// back the `GenericMaze` it holds.
+pub trait Shape: 'static {
+    /// Name of the grid type, as used on the command line and in saved mazes
+    fn name() -> &'static str;
+
+    /// Labels of the edges of the cell at (x, y), such as "N" or "SE", in
+    /// neighbor index order. A cell has one neighbor slot and one wall per
+    /// label, so cells of mixed tilings can have different numbers of
+    /// neighbors. Edges with the same label lead in the same direction.
+    fn edge_labels(width: usize, height: usize, x: usize, y: usize) -> &'static [&'static str];
 
     /// Initialize neighbor relationships for all cells
     fn init_neighbors(width: usize, height: usize, cells: &mut [MazeCell]);
 
-    /// Render the maze as SVG
-    fn to_svg(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool) -> String
+    /// Number of cells in row `y` of a grid `width` cells wide. Cells are
+    /// stored row by row; every row is `width` long unless a shape says otherwise.
+    fn row_len(width: usize, _y: usize) -> usize {
+        width
+    }
+
+    /// Width and height of the drawing, in drawing units
+    fn drawing_size(maze: &GenericMaze<Self>, tunnel_width: usize) -> (f64, f64)
+    where
+        Self: Sized;
+
+    /// Outline of a cell in drawing units
+    fn cell_polygon(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Vec<Point>
     where
         Self: Sized;
 
-    /// Print debug information (optional)
-    fn print_debug_info(maze: &GenericMaze<Self>)
+    /// Point inside a cell that the solution path runs through and debug
+    /// labels are centered on
+    fn cell_center(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Point
+    where
+        Self: Sized;
+
+    /// Corners of the cell's `cell_polygon` that wall `wall_idx` runs
+    /// between, or `None` if the cell has no wall in that slot or the wall
+    /// doesn't run corner to corner. `draw` builds each polygon once and
+    /// takes every such wall from it.
+    fn wall_corners(_maze: &GenericMaze<Self>, _idx: usize, _wall_idx: usize) -> Option<(usize, usize)>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Endpoints of wall `wall_idx` of a cell, or `None` if the cell has no
+    /// wall in that slot. By default the polygon corners `wall_corners` names.
+    fn wall_segment(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize, wall_idx: usize) -> Option<(Point, Point)>
+    where
+        Self: Sized,
+    {
+        let (from, to) = Self::wall_corners(maze, idx, wall_idx)?;
+        let points = Self::cell_polygon(maze, tunnel_width, idx);
+        Some((points[from], points[to]))
+    }
+
+    /// The circular arc wall `wall_idx` of a cell follows, for shapes with
+    /// curved walls; `wall_segment` still gives its endpoints. `None` (the
+    /// default) draws the wall straight.
+    fn wall_arc(_maze: &GenericMaze<Self>, _tunnel_width: usize, _idx: usize, _wall_idx: usize) -> Option<Arc>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Font size for debug labels
+    fn label_size() -> f64 {
+        12.0
+    }
+
+    /// Draw the maze's walls, optional solution path and (in debug mode) cell
+    /// labels onto a painter, from the geometry above
+    fn draw<P: Painter>(
+        maze: &GenericMaze<Self>,
+        tunnel_width: usize,
+        solution_path: Option<&[usize]>,
+        debug: bool,
+        painter: &mut P,
+    ) where
+        Self: Sized,
+    {
+        let (width, height) = Self::drawing_size(maze, tunnel_width);
+        painter.begin(width, height);
+
+        for idx in 0..maze.cells.len() {
+            let mut polygon = None;
+            for wall_idx in 0..maze.cells[idx].walls.len() {
+                if let Some(arc) = Self::wall_arc(maze, tunnel_width, idx, wall_idx) {
+                    maze.draw_arc_wall(painter, idx, wall_idx, &arc);
+                } else if let Some((from, to)) = Self::wall_corners(maze, idx, wall_idx) {
+                    let points = polygon.get_or_insert_with(|| Self::cell_polygon(maze, tunnel_width, idx));
+                    maze.draw_wall(painter, idx, wall_idx, points[from], points[to]);
+                } else if let Some((from, to)) = Self::wall_segment(maze, tunnel_width, idx, wall_idx) {
+                    maze.draw_wall(painter, idx, wall_idx, from, to);
+                }
+            }
+        }
+
+        if debug {
+            for idx in 0..maze.cells.len() {
+                painter.label(Self::cell_center(maze, tunnel_width, idx), Self::label_size(), &idx.to_string());
+            }
+        }
+
+        if let Some(path) = solution_path {
+            let points: Vec<Point> = path.iter().map(|&idx| Self::cell_center(maze, tunnel_width, idx)).collect();
+            painter.solution(&points);
+        }
+    }
+
+    /// Draw the maze as `draw` does, with the cells first filled as the
+    /// theme asks
+    fn paint<P: Painter>(
+        maze: &GenericMaze<Self>,
+        tunnel_width: usize,
+        solution_path: Option<&[usize]>,
+        debug: bool,
+        theme: &Theme,
+        painter: &mut P,
+    ) where
+        Self: Sized,
+    {
+        let fills = match theme.cell_fill {
+            CellFill::None => Vec::new(),
+            CellFill::Solid(color) => {
+                (0..maze.cells.len()).map(|idx| (Self::cell_polygon(maze, tunnel_width, idx), color.0)).collect()
+            }
+            CellFill::Heatmap => maze.heatmap(tunnel_width),
+        };
+        if fills.is_empty() {
+            Self::draw(maze, tunnel_width, solution_path, debug, painter);
+        } else {
+            Self::draw(maze, tunnel_width, solution_path, debug, &mut Underlay::new(painter, fills));
+        }
+    }
+
+    /// Render the maze as SVG
+    fn to_svg(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, theme: &Theme) -> String
+    where
+        Self: Sized,
+    {
+        let mut painter = SvgPainter::new(maze.seed, theme);
+        Self::paint(maze, tunnel_width, solution_path, debug, theme, &mut painter);
+        painter.finish()
+    }
+
+    /// Render the maze as a PNG image at the given resolution; 96 DPI gives
+    /// one pixel per SVG pixel. Fails if the image would be too large to allocate.
+    fn to_png(
+        maze: &GenericMaze<Self>,
+        tunnel_width: usize,
+        solution_path: Option<&[usize]>,
+        debug: bool,
+        theme: &Theme,
+        dpi: f64,
+    ) -> io::Result<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        let mut painter = RasterPainter::new(dpi, theme);
+        let (width, height) = Self::drawing_size(maze, tunnel_width);
+        painter.check_size(width, height)?;
+        Self::paint(maze, tunnel_width, solution_path, debug, theme, &mut painter);
+        Ok(painter.finish_png(maze.seed))
+    }
+
+    /// Render the maze as one page of vector PDF content; see `render::write_pdf`
+    fn pdf_page(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, theme: &Theme) -> PdfPage
+    where
+        Self: Sized,
+    {
+        let mut painter = PdfPainter::new(theme);
+        Self::paint(maze, tunnel_width, solution_path, debug, theme, &mut painter);
+        painter.finish()
+    }
+
+    /// Render the maze as merged pen strokes for plotters and laser cutters;
+    /// see `render::Plot`
+    fn to_plot(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>) -> Plot
+    where
+        Self: Sized,
+    {
+        let mut painter = PlotPainter::new();
+        Self::draw(maze, tunnel_width, solution_path, false, &mut painter);
+        painter.finish()
+    }
+
+    /// Render the maze as a DXF drawing in millimetres, at `dpi` drawing
+    /// units per inch
+    fn to_dxf(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool, dpi: f64) -> String
+    where
+        Self: Sized,
+    {
+        let mut painter = DxfPainter::new(dpi);
+        Self::draw(maze, tunnel_width, solution_path, debug, &mut painter);
+        painter.finish()
+    }
+
+    /// Render the maze as a binary STL model for 3D printing
+    fn to_stl(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, options: StlOptions) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut painter = StlPainter::new(options);
+        Self::draw(maze, tunnel_width, solution_path, false, &mut painter);
+        painter.finish(maze.seed)
+    }
+
+    /// Render the maze as plain text, with the solution path (if given)
+    /// traced in characters. By default the drawing is traced onto a
+    /// character grid; shapes can lay out their own text instead.
+    fn to_text(maze: &GenericMaze<Self>, solution_path: Option<&[usize]>, style: TextStyle) -> String
+    where
+        Self: Sized,
+    {
+        let mut painter = TextPainter::new(style);
+        Self::draw(maze, TextPainter::TUNNEL_WIDTH, solution_path, false, &mut painter);
+        painter.finish()
+    }
+
+    /// Wall indices of a cell that lie on the outer boundary of the grid, in
+    /// the order they are preferred when opening an entrance or exit there.
+    /// By default every wall without a neighbor, in index order.
+    fn boundary_walls(maze: &GenericMaze<Self>, idx: usize) -> Vec<usize>
+    where
+        Self: Sized,
+    {
+        maze.cells[idx]
+            .neighbors
+            .iter()
+            .enumerate()
+            .filter(|(_, neighbor)| neighbor.is_none())
+            .map(|(wall_idx, _)| wall_idx)
+            .collect()
+    }
+
+    /// Write debug information: every cell's neighbors, by edge label (optional)
+    fn print_debug_info(maze: &GenericMaze<Self>, out: &mut dyn io::Write) -> io::Result<()>
     where
         Self: Sized,
     {
-        println!("Grid: {}x{}", maze.width, maze.height);
-        println!("Total cells: {}", maze.cells.len());
+        writeln!(out, "\n=== Maze Debug Info ({} grid) ===", Self::name())?;
+        writeln!(out, "Grid: {}x{} (width x height)", maze.width, maze.height)?;
+        writeln!(out, "Total cells: {}", maze.cells.len())?;
+        writeln!(out, "\nNeighbor relationships:")?;
+
+        for idx in 0..maze.cells.len() {
+            let (x, y) = maze.cell_coords(idx);
+            let neighbors: Vec<String> = maze
+                .edge_labels(idx)
+                .iter()
+                .zip(&maze.cells[idx].neighbors)
+                .map(|(label, neighbor)| match neighbor {
+                    Some(n) => format!("{}={:2}", label, n),
+                    None => format!("{}=--", label),
+                })
+                .collect();
+            writeln!(out, "Cell {:2} (x={}, y={}): [{}]", idx, x, y, neighbors.join(", "))?;
+        }
+        Ok(())
     }
 }
 
@@ -70,128 +352,264 @@ pub trait Shape {
 pub struct GenericMaze<S: Shape> {
     pub width: usize,
     pub height: usize,
-    pub cells: Vec<MazeCell>,
+    pub(crate) cells: Vec<MazeCell>,
+    /// Seed used to generate the maze, if it was generated from one
+    pub seed: Option<u64>,
+    /// Cell the solution starts from (defaults to the first cell)
+    pub entrance: usize,
+    /// Cell the solution ends at (defaults to the last cell)
+    pub exit: usize,
+    /// Boundary wall opened at the entrance; `None` uses `opening_wall`
+    pub entrance_wall: Option<usize>,
+    /// Boundary wall opened at the exit; `None` uses `opening_wall`
+    pub exit_wall: Option<usize>,
+    /// Index of the first cell of each row, plus the total cell count
+    row_starts: Vec<usize>,
     _shape: std::marker::PhantomData<S>,
 }
 
 impl<S: Shape> GenericMaze<S> {
     /// Create a new maze with initialized neighbor relationships
     pub fn new(width: usize, height: usize) -> Self {
-        let num_cells = width * height;
-        let mut cells = vec![MazeCell::new(S::num_neighbors()); num_cells];
+        let mut row_starts = vec![0];
+        for y in 0..height {
+            row_starts.push(row_starts[y] + S::row_len(width, y));
+        }
+        let num_cells = row_starts[height];
+        let mut cells: Vec<MazeCell> = (0..height)
+            .flat_map(|y| (0..S::row_len(width, y)).map(move |x| MazeCell::new(S::edge_labels(width, height, x, y).len())))
+            .collect();
         S::init_neighbors(width, height, &mut cells);
 
         GenericMaze {
             width,
             height,
             cells,
+            seed: None,
+            entrance: 0,
+            exit: num_cells.saturating_sub(1),
+            entrance_wall: None,
+            exit_wall: None,
+            row_starts,
             _shape: std::marker::PhantomData,
         }
     }
 
+    /// Number of cells in a `width` x `height` maze, or `None` if there are
+    /// more than `limit`. Counts row by row and stops early, so sizes read
+    /// from a file can be checked against its contents before `new` allocates.
+    pub fn cell_count(width: usize, height: usize, limit: usize) -> Option<usize> {
+        (0..height).try_fold(0usize, |count, y| count.checked_add(S::row_len(width, y)).filter(|&count| count <= limit))
+    }
+
+    /// Every cell, row by row
+    pub fn cells(&self) -> &[MazeCell] {
+        &self.cells
+    }
+
     /// Convert (x, y) coordinates to cell index
     pub fn cell_index(&self, x: usize, y: usize) -> usize {
-        cell_index(x, y, self.width)
+        self.row_starts[y] + x
     }
 
     /// Convert cell index to (x, y) coordinates
     pub fn cell_coords(&self, idx: usize) -> (usize, usize) {
-        cell_coords(idx, self.width)
+        let y = self.row_starts.partition_point(|&start| start <= idx) - 1;
+        (idx - self.row_starts[y], y)
     }
 
-    /// Generate the maze (easy = long corridors, hard = more branching)
-    pub fn generate(&mut self, is_hard: bool) {
-        use std::collections::HashSet;
-        let mut rng = rand::thread_rng();
-        let mut visited = vec![false; self.cells.len()];
+    /// Number of cells in row `y` (see `Shape::row_len`)
+    pub fn row_len(&self, y: usize) -> usize {
+        self.row_starts[y + 1] - self.row_starts[y]
+    }
+
+    /// Labels of a cell's edges, in neighbor index order (see `Shape::edge_labels`)
+    pub fn edge_labels(&self, idx: usize) -> &'static [&'static str] {
+        let (x, y) = self.cell_coords(idx);
+        S::edge_labels(self.width, self.height, x, y)
+    }
+
+    /// Generate the maze with the given algorithm, driven by the supplied
+    /// random number generator. Using a seeded RNG (see `seeded_rng`) makes
+    /// the result reproducible.
+    pub fn generate_with_rng<G: Generator, R: Rng + ?Sized>(&mut self, generator: &G, rng: &mut R) {
+        generator.generate(self, rng);
+    }
+
+    /// Iterate over the `(edge_idx, neighbor)` pairs of a cell that have a neighbor
+    pub fn neighbors_of(&self, idx: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells[idx]
+            .neighbors
+            .iter()
+            .enumerate()
+            .filter_map(|(edge_idx, &neighbor)| neighbor.map(|n| (edge_idx, n)))
+    }
+
+    /// Remove the wall on edge `edge_idx` of a cell, along with the matching
+    /// wall on the neighbor's side
+    pub fn carve(&mut self, idx: usize, edge_idx: usize) {
+        let Some(next) = self.cells[idx].neighbors[edge_idx] else { return };
+        self.cells[idx].walls[edge_idx] = false;
 
-        // Strategy pattern: different frontier management for easy vs hard
-        enum Frontier {
-            Stack(Vec<usize>),           // Easy: LIFO (last-in-first-out) creates long corridors
-            Set(HashSet<usize>),         // Hard: random selection creates more branching
+        // Find and remove reverse edge
+        for (rev_idx, &neighbor_opt) in self.cells[next].neighbors.iter().enumerate() {
+            if neighbor_opt == Some(idx) {
+                self.cells[next].walls[rev_idx] = false;
+                break;
+            }
         }
+    }
 
-        let mut frontier = if is_hard {
-            Frontier::Set(HashSet::new())
-        } else {
-            Frontier::Stack(Vec::new())
-        };
+    /// Iterate over the `(edge_idx, neighbor)` pairs of a cell that are open passages
+    pub fn passages_of(&self, idx: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors_of(idx).filter(move |&(edge_idx, _)| !self.cells[idx].walls[edge_idx])
+    }
 
-        // Add starting cell to frontier
-        match &mut frontier {
-            Frontier::Stack(stack) => stack.push(0),
-            Frontier::Set(set) => { set.insert(0); },
-        }
-        visited[0] = true;
-
-        // Main generation loop
-        loop {
-            // Pick next cell from frontier based on strategy
-            let current = match &frontier {
-                Frontier::Stack(stack) => stack.last().copied(),
-                Frontier::Set(set) => {
-                    if set.is_empty() {
-                        None
-                    } else {
-                        let vec: Vec<usize> = set.iter().copied().collect();
-                        vec.choose(&mut rng).copied()
-                    }
-                }
-            };
+    /// True if the cell has exactly one open passage
+    pub fn is_dead_end(&self, idx: usize) -> bool {
+        self.passages_of(idx).count() == 1
+    }
 
-            let Some(current) = current else { break };
+    /// Remove roughly `ratio` (0.0..=1.0) of the dead ends by knocking out a
+    /// wall into a neighboring cell, adding loops to the maze. Walls into other
+    /// dead ends are preferred since that removes two dead ends at once.
+    pub fn braid<R: Rng + ?Sized>(&mut self, ratio: f64, rng: &mut R) {
+        let mut dead_ends: Vec<usize> = (0..self.cells.len()).filter(|&idx| self.is_dead_end(idx)).collect();
+        dead_ends.shuffle(rng);
+        let target = (dead_ends.len() as f64 * ratio.clamp(0.0, 1.0)).round() as usize;
+
+        for &idx in &dead_ends[..target] {
+            // May already have been opened up by an earlier knock-out
+            if !self.is_dead_end(idx) {
+                continue;
+            }
 
-            // Find unvisited neighbors
-            let mut unvisited = Vec::new();
-            for (edge_idx, &neighbor_opt) in self.cells[current].neighbors.iter().enumerate() {
-                if let Some(neighbor) = neighbor_opt {
-                    if !visited[neighbor] {
-                        unvisited.push((neighbor, edge_idx));
+            let walled: Vec<(usize, usize)> = self
+                .neighbors_of(idx)
+                .filter(|&(edge_idx, _)| self.cells[idx].walls[edge_idx])
+                .collect();
+            let preferred: Vec<(usize, usize)> = walled
+                .iter()
+                .copied()
+                .filter(|&(_, neighbor)| self.is_dead_end(neighbor))
+                .collect();
+            let candidates = if preferred.is_empty() { &walled } else { &preferred };
+
+            if let Some(&(edge_idx, _)) = candidates.choose(rng) {
+                self.carve(idx, edge_idx);
+            }
+        }
+    }
+
+    /// Check that every wall is recorded consistently: shared walls agree on
+    /// both sides and walls without a neighbor (the outline) are present
+    pub fn check_walls(&self) -> Result<(), String> {
+        for (idx, cell) in self.cells.iter().enumerate() {
+            for (edge_idx, neighbor) in cell.neighbors.iter().enumerate() {
+                match *neighbor {
+                    None if !cell.walls[edge_idx] => {
+                        return Err(format!("cell {} has an opening in its outer wall {}", idx, edge_idx));
                     }
+                    Some(next) => {
+                        let back = self.cells[next].neighbors.iter().position(|&n| n == Some(idx));
+                        if back.map(|rev_idx| self.cells[next].walls[rev_idx]) != Some(cell.walls[edge_idx]) {
+                            return Err(format!("wall between cells {} and {} does not match on both sides", idx, next));
+                        }
+                    }
+                    None => {}
                 }
             }
+        }
+        Ok(())
+    }
 
-            if unvisited.is_empty() {
-                // No unvisited neighbors, remove from frontier
-                match &mut frontier {
-                    Frontier::Stack(stack) => { stack.pop(); },
-                    Frontier::Set(set) => { set.remove(&current); },
-                }
-            } else {
-                // Pick a random unvisited neighbor
-                let &(next, edge_idx) = unvisited.choose(&mut rng).unwrap();
+    /// The boundary wall the shape prefers to leave open for a cell used as
+    /// entrance or exit, if it has one
+    pub fn opening_wall(&self, idx: usize) -> Option<usize> {
+        S::boundary_walls(self, idx).first().copied()
+    }
 
-                // Carve passage between current and next
-                self.cells[current].walls[edge_idx] = false;
+    /// True if this wall is the gap for the entrance or exit and should not be drawn
+    pub fn is_opening(&self, idx: usize, wall_idx: usize) -> bool {
+        let opened = |endpoint: usize, wall: Option<usize>| idx == endpoint && wall.or_else(|| self.opening_wall(idx)) == Some(wall_idx);
+        opened(self.entrance, self.entrance_wall) || opened(self.exit, self.exit_wall)
+    }
 
-                // Find and remove reverse edge
-                for (rev_idx, &neighbor_opt) in self.cells[next].neighbors.iter().enumerate() {
-                    if neighbor_opt == Some(current) {
-                        self.cells[next].walls[rev_idx] = false;
-                        break;
-                    }
-                }
+    /// Draw one wall of a cell from `from` to `to`, if it is standing. The
+    /// entrance and exit openings are skipped, and walls on the outline of
+    /// the grid are drawn as the border.
+    pub fn draw_wall<P: Painter>(&self, painter: &mut P, idx: usize, wall_idx: usize, from: Point, to: Point) {
+        if !self.cells[idx].walls[wall_idx] || self.is_opening(idx, wall_idx) {
+            return;
+        }
+        if self.cells[idx].neighbors[wall_idx].is_none() {
+            painter.border(from, to);
+        } else {
+            painter.wall(from, to);
+        }
+    }
+
+    /// Draw one wall of a cell along an arc, skipping and bordering it as
+    /// `draw_wall` does
+    pub fn draw_arc_wall<P: Painter>(&self, painter: &mut P, idx: usize, wall_idx: usize, arc: &Arc) {
+        if !self.cells[idx].walls[wall_idx] || self.is_opening(idx, wall_idx) {
+            return;
+        }
+        if self.cells[idx].neighbors[wall_idx].is_none() {
+            painter.arc_border(arc);
+        } else {
+            painter.arc_wall(arc);
+        }
+    }
+
+    /// BFS distance (in steps through open passages) from `start` to every
+    /// cell; `None` for cells that cannot be reached
+    pub fn distances_from(&self, start: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.cells.len()];
+        let mut queue = VecDeque::new();
+        distances[start] = Some(0);
+        queue.push_back(start);
 
-                // Mark as visited and add to frontier
-                visited[next] = true;
-                match &mut frontier {
-                    Frontier::Stack(stack) => stack.push(next),
-                    Frontier::Set(set) => { set.insert(next); },
+        while let Some(current) = queue.pop_front() {
+            let next_distance = distances[current].map(|d| d + 1);
+            for (_, neighbor) in self.passages_of(current) {
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = next_distance;
+                    queue.push_back(neighbor);
                 }
             }
         }
+
+        distances
+    }
+
+    /// Each reachable cell's outline, coloured by its distance from the
+    /// entrance along the heatmap ramp
+    pub fn heatmap(&self, tunnel_width: usize) -> Vec<(Vec<Point>, Rgb)> {
+        let distances = self.distances_from(self.entrance);
+        let farthest = distances.iter().flatten().copied().max().unwrap_or(0).max(1);
+        distances
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, distance)| {
+                let color = heat_color((*distance)? as f64 / farthest as f64);
+                Some((S::cell_polygon(self, tunnel_width, idx), color))
+            })
+            .collect()
     }
 
-    /// Solve the maze using BFS
+    /// Solve the maze from entrance to exit using BFS. Returns the shortest path, so it stays
+    /// correct for braided mazes that contain loops.
     pub fn solve(&self) -> Vec<usize> {
         let mut queue = VecDeque::new();
         let mut visited = vec![false; self.cells.len()];
         let mut parent: HashMap<usize, usize> = HashMap::new();
 
-        let end = self.cells.len() - 1;
+        let start = self.entrance;
+        let end = self.exit;
 
-        queue.push_back(0);
-        visited[0] = true;
+        queue.push_back(start);
+        visited[start] = true;
 
         while let Some(current) = queue.pop_front() {
             if current == end {
@@ -199,12 +617,13 @@ impl<S: Shape> GenericMaze<S> {
             }
 
             for (edge_idx, &neighbor_opt) in self.cells[current].neighbors.iter().enumerate() {
-                if let Some(neighbor) = neighbor_opt {
-                    if !self.cells[current].walls[edge_idx] && !visited[neighbor] {
-                        visited[neighbor] = true;
-                        parent.insert(neighbor, current);
-                        queue.push_back(neighbor);
-                    }
+                if let Some(neighbor) = neighbor_opt
+                    && !self.cells[current].walls[edge_idx]
+                    && !visited[neighbor]
+                {
+                    visited[neighbor] = true;
+                    parent.insert(neighbor, current);
+                    queue.push_back(neighbor);
                 }
             }
         }
@@ -213,7 +632,7 @@ impl<S: Shape> GenericMaze<S> {
         let mut current = end;
         path.push(current);
 
-        while current != 0 {
+        while current != start {
             if let Some(&prev) = parent.get(&current) {
                 path.push(prev);
                 current = prev;
@@ -226,3 +645,117 @@ impl<S: Shape> GenericMaze<S> {
         path
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::Algorithm;
+    use crate::generators::GrowingTree;
+    use crate::shapes::{OctShape, RectShape};
+    use crate::testutil::{build, options, shape_tests};
+
+    fn walls<S: Shape>(seed: u64, is_hard: bool) -> Vec<Vec<bool>> {
+        let mut maze = GenericMaze::<S>::new(12, 9);
+        maze.generate_with_rng(&GrowingTree::for_difficulty(is_hard), &mut seeded_rng(seed));
+        maze.cells.into_iter().map(|cell| cell.walls).collect()
+    }
+
+    fn test_same_seed_same_maze<S: Shape>() {
+        for is_hard in [false, true] {
+            assert!(walls::<S>(42, is_hard) == walls::<S>(42, is_hard));
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        assert!(walls::<RectShape>(42, false) != walls::<RectShape>(43, false));
+        assert!(walls::<RectShape>(42, true) != walls::<RectShape>(43, true));
+    }
+
+    fn test_full_braid_removes_dead_ends<S: Shape>() {
+        let mut options = options::<S>(15, 15, 11);
+        options.algorithm = Algorithm::GrowingTree(GrowingTree::for_difficulty(true));
+        options.braid = 1.0;
+        let maze = build::<S>(&options);
+
+        // Cells with a single neighbor (e.g. triangular corners) are dead ends by construction
+        assert!((0..maze.cells.len()).all(|idx| !maze.is_dead_end(idx) || maze.neighbors_of(idx).count() == 1));
+
+        // With loops present the solver must still find a path of open passages
+        let path = maze.solve();
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&(maze.cells.len() - 1)));
+        for pair in path.windows(2) {
+            assert!(maze.passages_of(pair[0]).any(|(_, n)| n == pair[1]));
+        }
+    }
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    fn test_edge_label_per_wall<S: Shape>() {
+        let maze = GenericMaze::<S>::new(6, 5);
+        assert!((0..maze.cells.len()).all(|idx| maze.edge_labels(idx).len() == maze.cells[idx].walls.len()));
+    }
+
+    fn test_cell_centers_inside_drawing<S: Shape>() {
+        let maze = GenericMaze::<S>::new(6, 5);
+        let (width, height) = S::drawing_size(&maze, 20);
+        for idx in 0..maze.cells.len() {
+            let (x, y) = S::cell_center(&maze, 20, idx);
+            assert!(x > 0.0 && x < width && y > 0.0 && y < height, "cell {}", idx);
+        }
+    }
+
+    fn test_walls_lie_on_cell_outline<S: Shape>() {
+        // Every wall runs between points of the cell's outline, and only edges
+        // without a neighbor may have no wall
+        let maze = GenericMaze::<S>::new(6, 5);
+        for idx in 0..maze.cells.len() {
+            let polygon = S::cell_polygon(&maze, 20, idx);
+            for (wall_idx, neighbor) in maze.cells[idx].neighbors.iter().enumerate() {
+                match S::wall_segment(&maze, 20, idx, wall_idx) {
+                    Some((from, to)) => {
+                        assert!(polygon.iter().any(|&p| close(p, from)) && polygon.iter().any(|&p| close(p, to)), "cell {}", idx)
+                    }
+                    None => assert!(neighbor.is_none(), "cell {}", idx),
+                }
+            }
+        }
+    }
+
+    fn test_neighbors_share_walls<S: Shape>() {
+        // Neighbors see their shared wall as the same segment
+        let maze = GenericMaze::<S>::new(6, 5);
+        for idx in 0..maze.cells.len() {
+            for (wall_idx, neighbor) in maze.cells[idx].neighbors.iter().enumerate() {
+                let Some(next) = *neighbor else {
+                    continue;
+                };
+                let (from, to) = S::wall_segment(&maze, 20, idx, wall_idx).unwrap();
+                let back = maze.cells[next].neighbors.iter().position(|&n| n == Some(idx)).unwrap();
+                let (a, b) = S::wall_segment(&maze, 20, next, back).unwrap();
+                assert!((close(a, from) && close(b, to)) || (close(a, to) && close(b, from)), "cell {}", idx);
+            }
+        }
+    }
+
+    shape_tests!(
+        test_same_seed_same_maze,
+        test_full_braid_removes_dead_ends,
+        test_edge_label_per_wall,
+        test_cell_centers_inside_drawing,
+        test_walls_lie_on_cell_outline,
+        test_neighbors_share_walls,
+    );
+
+    #[test]
+    fn test_octagonal_squares_have_four_walls() {
+        // Octagonal squares have only the four walls they draw
+        let oct = GenericMaze::<OctShape>::new(2, 1);
+        assert_eq!(oct.edge_labels(0).len(), 8);
+        assert_eq!(oct.edge_labels(1), ["N", "S", "E", "W"]);
+        assert_eq!(oct.cells[1].walls.len(), 4);
+    }
+}
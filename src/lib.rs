@@ -0,0 +1,54 @@
+//! Maze generation, solving and rendering on rectangular, triangular,
+//! hexagonal, octagonal and polar grids.
+//!
+//! [`generate`] builds a maze the way the command line does: a shape picked
+//! by name, carved from a seeded random stream, with its entrance and exit
+//! placed and regenerated until any difficulty constraints are met. The
+//! result is an [`AnyMaze`], which can be solved and rendered as SVG, PNG,
+//! PDF, plotter SVG, DXF, STL or text:
+//!
+//! ```
+//! use maze::{generate, GenerateOptions, Theme};
+//!
+//! let mut options = GenerateOptions::new("hexagonal", 12, 8);
+//! options.seed = Some(42);
+//! let (maze, _attempts) = generate(&options)?;
+//!
+//! let solution = maze.solve();
+//! assert_eq!(solution.first(), Some(&maze.entrance()));
+//! let svg = maze.to_svg(20, Some(&solution), false, &Theme::default());
+//! assert!(svg.contains("<metadata>seed=42</metadata>"));
+//! # Ok::<(), maze::GenerateError>(())
+//! ```
+//!
+//! Code written for one shape can use [`GenericMaze`] over that [`Shape`]
+//! directly, carving it with any [`Generator`] and rendering it with the
+//! shape's `to_svg`, `to_png`, `pdf_page`, `to_plot`, `to_dxf`, `to_stl` or
+//! `to_text`.
+//!
+//! The `maze` binary is a command-line front end over this crate.
+
+mod anymaze;
+mod endpoints;
+mod generate;
+mod generators;
+mod genericmaze;
+mod mazefile;
+mod packed;
+pub mod render;
+mod shapes;
+mod stats;
+mod textmaze;
+#[cfg(test)]
+mod testutil;
+
+pub use anymaze::AnyMaze;
+pub use endpoints::{longest_boundary_path, place_endpoints, place_longest_path, Endpoint, Side};
+pub use generate::{generate, Algorithm, GenerateError, GenerateOptions};
+pub use generators::{AldousBroder, Generator, GrowingTree, HuntAndKill, Kruskal, Prim, Wilson};
+pub use genericmaze::{seeded_rng, GenericMaze, MazeCell, MazeRng, Shape};
+pub use mazefile::{load_saved, saved_grid_type, MazeFile, FORMAT_VERSION};
+pub use packed::{from_packed, save_packed, to_packed, PACKED_VERSION};
+pub use render::{Painter, Theme};
+pub use shapes::{HexShape, OctShape, PolarShape, RectShape, TriShape};
+pub use stats::{Constraints, MazeStats};
@@ -1,128 +1,671 @@
-use clap::{Parser, ValueEnum};
+use clap::builder::PossibleValuesParser;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use std::fs::File;
 use std::io::Write;
 
-mod genericmaze;
-mod shapes;
+use maze::{generate, GenerateOptions, GrowingTree};
+use maze::render::{
+    mm_to_points, write_pdf, CellFill, Color, GcodeOptions, Origin, PageSize, PdfPage, PenCommand, StlOptions, TextStyle, Theme,
+    Units,
+};
+use maze::{AnyMaze, Constraints, Endpoint};
 
-use genericmaze::{GenericMaze, Shape};
-use shapes::{RectShape, TriShape, HexShape, OctShape};
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Difficulty {
+    Easy,
+    Hard,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
-enum GridType {
-    Rectangular,
-    Triangular,
-    Hexagonal,
-    Octagonal,
+enum Algorithm {
+    GrowingTree,
+    Kruskal,
+    Prim,
+    Wilson,
+    AldousBroder,
+    HuntAndKill,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
-enum Difficulty {
-    Easy,
-    Hard,
+enum OutputFormat {
+    Svg,
+    Png,
+    Pdf,
+    Text,
+    /// SVG of merged strokes for pen plotters and laser cutters
+    Plot,
+    Gcode,
+    Dxf,
+    Stl,
 }
 
-#[derive(Parser)]
-#[command(name = "maze")]
-#[command(about = "Generate a maze in SVG format", long_about = None)]
-struct Args {
-    /// Width of the maze in cells
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Svg => "svg",
+            OutputFormat::Png => "png",
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Text => "txt",
+            OutputFormat::Plot => "svg",
+            OutputFormat::Gcode => "gcode",
+            OutputFormat::Dxf => "dxf",
+            OutputFormat::Stl => "stl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum StatsFormat {
+    Text,
+    Json,
+}
+
+/// Options that determine which maze gets generated, shared by all commands
+#[derive(clap::Args, Clone)]
+struct MazeArgs {
+    /// Width of the maze in cells (polar: cells in the innermost ring)
     #[arg(short = 'W', long)]
     width: usize,
 
-    /// Height of the maze in cells
+    /// Height of the maze in cells (polar: number of rings)
     #[arg(short = 'H', long)]
     height: usize,
 
-    /// Output SVG file path
+    /// Grid type (default: rectangular)
+    #[arg(short, long, default_value = "rectangular", value_parser = PossibleValuesParser::new(AnyMaze::grid_types()))]
+    grid_type: String,
+
+    /// Difficulty: easy (long corridors) or hard (more branching); growing-tree only (default: easy)
+    #[arg(short = 'D', long, value_enum)]
+    difficulty: Option<Difficulty>,
+
+    /// Random seed; the same seed and options always produce the same maze (default: random)
+    #[arg(short, long)]
+    seed: Option<u64>,
+
+    /// Generation algorithm (default: growing-tree)
+    #[arg(short, long, value_enum, default_value = "growing-tree")]
+    algorithm: Algorithm,
+
+    /// Growing-tree cell selection weights, e.g. "newest=3,random=1"; growing-tree only (default: set by difficulty)
+    #[arg(long)]
+    selection: Option<GrowingTree>,
+
+    /// Fraction of dead ends to remove by adding loops, 0.0 (perfect maze) to 1.0 (no dead ends)
+    #[arg(short, long, default_value = "0.0", value_parser = parse_ratio)]
+    braid: f64,
+
+    /// Entrance cell: "X,Y", a side (north, south, east, west) or "farthest" from the exit (default: first cell)
+    #[arg(long)]
+    entrance: Option<Endpoint>,
+
+    /// Exit cell: "X,Y", a side (north, south, east, west) or "farthest" from the entrance (default: last cell)
+    #[arg(long)]
+    exit: Option<Endpoint>,
+
+    /// Place entrance and exit on the boundary cells with the longest solution between them
+    #[arg(short, long, default_value = "false", conflicts_with_all = ["entrance", "exit"])]
+    longest_path: bool,
+
+    /// Regenerate until the solution covers at least this fraction of cells
+    #[arg(long, value_parser = parse_ratio)]
+    min_solution_ratio: Option<f64>,
+
+    /// Regenerate until the solution covers at most this fraction of cells
+    #[arg(long, value_parser = parse_ratio)]
+    max_solution_ratio: Option<f64>,
+
+    /// Regenerate until the maze has at least this many dead ends
+    #[arg(long)]
+    min_dead_ends: Option<usize>,
+
+    /// Regenerate until the maze has at most this many dead ends
+    #[arg(long)]
+    max_dead_ends: Option<usize>,
+
+    /// Give up after this many attempts at meeting the constraints (default: 1000)
+    #[arg(long, default_value = "1000")]
+    max_attempts: usize,
+}
+
+impl MazeArgs {
+    /// The library's generation options for these arguments
+    fn options(&self) -> GenerateOptions {
+        let algorithm = match self.algorithm {
+            Algorithm::GrowingTree => maze::Algorithm::GrowingTree(
+                self.selection.unwrap_or_else(|| GrowingTree::for_difficulty(self.difficulty == Some(Difficulty::Hard))),
+            ),
+            Algorithm::Kruskal => maze::Algorithm::Kruskal,
+            Algorithm::Prim => maze::Algorithm::Prim,
+            Algorithm::Wilson => maze::Algorithm::Wilson,
+            Algorithm::AldousBroder => maze::Algorithm::AldousBroder,
+            Algorithm::HuntAndKill => maze::Algorithm::HuntAndKill,
+        };
+        GenerateOptions {
+            grid_type: self.grid_type.clone(),
+            width: self.width,
+            height: self.height,
+            algorithm,
+            seed: self.seed,
+            braid: self.braid,
+            entrance: self.entrance,
+            exit: self.exit,
+            longest_path: self.longest_path,
+            constraints: self.constraints(),
+            max_attempts: self.max_attempts,
+        }
+    }
+
+    fn constraints(&self) -> Constraints {
+        Constraints {
+            min_solution_ratio: self.min_solution_ratio,
+            max_solution_ratio: self.max_solution_ratio,
+            min_dead_ends: self.min_dead_ends,
+            max_dead_ends: self.max_dead_ends,
+        }
+    }
+}
+
+/// Options that control how a maze is rendered to files
+#[derive(clap::Args)]
+struct OutputArgs {
+    /// Output file path; the solution is written alongside it with a "_solution" suffix.
+    /// "-" prints text output, followed by the solution, to stdout
     #[arg(short, long)]
     output: String,
 
+    /// Output format: svg, png, pdf, text, plot (SVG with merged strokes for plotters and laser cutters)
+    /// gcode, dxf or stl (default: from the output file extension, otherwise svg)
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Resolution for PNG output, and drawing units per inch for G-code, DXF and STL; 96 DPI gives one pixel per SVG pixel (default: 96)
+    #[arg(long, default_value = "96", value_parser = |s: &str| parse_positive(s, "resolution"))]
+    dpi: f64,
+
+    /// Paper size for PDF output: letter, legal, a4, a3, or e.g. "200x300mm" or "8.5x11in" (default: letter)
+    #[arg(long, default_value = "letter")]
+    page: PageSize,
+
+    /// Page margin for PDF output in millimetres; the maze is scaled to fit inside it (default: 10)
+    #[arg(long, default_value = "10", value_parser = |s: &str| parse_non_negative(s, "margin"))]
+    margin: f64,
+
+    /// For PDF output, put the solution on a second page instead of in a separate file
+    #[arg(long, default_value = "false")]
+    solution_page: bool,
+
+    /// G-code units: mm or in (default: mm)
+    #[arg(long, default_value = "mm")]
+    units: Units,
+
+    /// G-code origin: bottom-left, top-left or center of the maze (default: bottom-left)
+    #[arg(long, default_value = "bottom-left")]
+    origin: Origin,
+
+    /// G-code feed rate for drawing or cutting moves, in units per minute (default: 1000)
+    #[arg(long, default_value = "1000", value_parser = |s: &str| parse_positive(s, "feed rate"))]
+    feed_rate: f64,
+
+    /// G-code to lift the pen: a Z height, or a command such as "M5" (default: 5)
+    #[arg(long, default_value = "5", allow_hyphen_values = true)]
+    pen_up: PenCommand,
+
+    /// G-code to lower the pen: a Z height (negative to cut into the stock), or a command such as "M3 S90" (default: 0)
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    pen_down: PenCommand,
+
+    /// For G-code output, put only the solution in the solution file, as a separate job
+    #[arg(long, default_value = "false")]
+    separate_solution: bool,
+
+    /// STL wall height above the base plate, in millimetres (default: 10)
+    #[arg(long, default_value = "10", value_parser = |s: &str| parse_positive(s, "size"))]
+    wall_height: f64,
+
+    /// STL wall thickness in millimetres; must be less than the tunnel width (default: 1.5)
+    #[arg(long, default_value = "1.5", value_parser = |s: &str| parse_positive(s, "size"))]
+    wall_thickness: f64,
+
+    /// STL base plate thickness in millimetres; 0 for free-standing walls (default: 2)
+    #[arg(long, default_value = "2", value_parser = |s: &str| parse_non_negative(s, "thickness"))]
+    base_thickness: f64,
+
+    /// Characters for text output: ascii or unicode (box drawing) (default: ascii)
+    #[arg(long, default_value = "ascii")]
+    text_style: TextStyle,
+
     /// Tunnel width in pixels (default: 20)
     #[arg(short, long, default_value = "20")]
     tunnel_width: usize,
 
-    /// Grid type: rectangular, triangular, hexagonal, or octagonal (default: rectangular)
-    #[arg(short, long, value_enum, default_value = "rectangular")]
-    grid_type: GridType,
+    /// JSON theme file for SVG, PNG, PDF and plot output, e.g. {"wall_color": "navy"}; the flags below override it
+    #[arg(long)]
+    theme: Option<String>,
+
+    /// Background colour: a name such as white or a hex value such as #f4ecd8 (default: white)
+    #[arg(long)]
+    background: Option<Color>,
+
+    /// Wall colour (default: black)
+    #[arg(long)]
+    wall_color: Option<Color>,
+
+    /// Wall stroke width in drawing units (default: 2)
+    #[arg(long, value_parser = |s: &str| parse_positive(s, "size"))]
+    wall_width: Option<f64>,
+
+    /// Solution colour (default: red)
+    #[arg(long)]
+    solution_color: Option<Color>,
+
+    /// Solution stroke width in drawing units (default: 3)
+    #[arg(long, value_parser = |s: &str| parse_positive(s, "size"))]
+    solution_width: Option<f64>,
+
+    /// Solution dash pattern as comma-separated dash and gap lengths, e.g. "6,4" (default: solid)
+    #[arg(long, value_delimiter = ',', value_parser = |s: &str| parse_positive(s, "size"))]
+    solution_dash: Option<Vec<f64>>,
+
+    /// Debug label colour (default: blue)
+    #[arg(long)]
+    label_color: Option<Color>,
+
+    /// Debug label font family for SVG output (default: monospace)
+    #[arg(long)]
+    label_font: Option<String>,
+
+    /// Cell fill: none, heatmap (shaded by distance from the entrance) or a colour (default: none)
+    #[arg(long)]
+    cell_fill: Option<CellFill>,
+
+    /// Shorthand for --cell-fill heatmap
+    #[arg(long, default_value = "false", conflicts_with = "cell_fill")]
+    heatmap: bool,
 
     /// Enable debug mode (show cell numbers and print neighbor info)
     #[arg(short, long, default_value = "false")]
     debug: bool,
+}
+
+impl OutputArgs {
+    /// Where progress messages go: stderr when stdout carries the maze itself
+    fn status(&self) -> Box<dyn Write> {
+        if self.output == "-" { Box::new(std::io::stderr()) } else { Box::new(std::io::stdout()) }
+    }
+
+    fn gcode_options(&self) -> GcodeOptions {
+        GcodeOptions {
+            dpi: self.dpi,
+            units: self.units,
+            origin: self.origin,
+            feed_rate: self.feed_rate,
+            pen_up: self.pen_up.clone(),
+            pen_down: self.pen_down.clone(),
+        }
+    }
+
+    fn stl_options(&self) -> StlOptions {
+        StlOptions {
+            dpi: self.dpi,
+            wall_height: self.wall_height,
+            wall_thickness: self.wall_thickness,
+            base_thickness: self.base_thickness,
+        }
+    }
+
+    /// The theme file, if any, with the theme flags applied on top
+    fn theme(&self) -> std::io::Result<Theme> {
+        let mut theme = match &self.theme {
+            Some(path) => Theme::load(path)
+                .map_err(|e| std::io::Error::new(e.kind(), format!("could not load theme {}: {}", path, e)))?,
+            None => Theme::default(),
+        };
+        if let Some(color) = self.background {
+            theme.background = color;
+        }
+        if let Some(color) = self.wall_color {
+            theme.wall_color = color;
+        }
+        if let Some(width) = self.wall_width {
+            theme.wall_width = width;
+        }
+        if let Some(color) = self.solution_color {
+            theme.solution_color = color;
+        }
+        if let Some(width) = self.solution_width {
+            theme.solution_width = width;
+        }
+        if let Some(dash) = &self.solution_dash {
+            theme.solution_dash.clone_from(dash);
+        }
+        if let Some(color) = self.label_color {
+            theme.label_color = color;
+        }
+        if let Some(font) = &self.label_font {
+            theme.label_font.clone_from(font);
+        }
+        if let Some(fill) = self.cell_fill {
+            theme.cell_fill = fill;
+        }
+        if self.heatmap {
+            theme.cell_fill = CellFill::Heatmap;
+        }
+        Ok(theme)
+    }
+
+    fn format(&self) -> OutputFormat {
+        self.format.unwrap_or_else(|| {
+            let output = self.output.to_ascii_lowercase();
+            if output.ends_with(".png") {
+                OutputFormat::Png
+            } else if output.ends_with(".pdf") {
+                OutputFormat::Pdf
+            } else if output.ends_with(".gcode") {
+                OutputFormat::Gcode
+            } else if output.ends_with(".dxf") {
+                OutputFormat::Dxf
+            } else if output.ends_with(".stl") {
+                OutputFormat::Stl
+            } else if output.ends_with(".txt") || output == "-" {
+                OutputFormat::Text
+            } else {
+                OutputFormat::Svg
+            }
+        })
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "maze")]
+#[command(about = "Generate mazes as SVG, PNG, PDF, text, plotter SVG, G-code, DXF or STL; re-render saved ones, or report their statistics", long_about = None)]
+struct Args {
+    #[command(flatten)]
+    maze: MazeArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
 
     /// Render all walls (skip maze generation)
     #[arg(long, default_value = "false")]
     all_walls: bool,
 
-    /// Difficulty: easy (long corridors) or hard (more branching) (default: easy)
-    #[arg(short = 'D', long, value_enum, default_value = "easy")]
-    difficulty: Difficulty,
+    /// Also save the maze for re-rendering later with `render`: JSON (with solution), or the
+    /// compact binary format if the path ends in .mazb
+    #[arg(long, conflicts_with = "all_walls")]
+    save: Option<String>,
 }
 
-fn main() -> std::io::Result<()> {
-    let args = Args::parse();
+#[derive(Subcommand)]
+enum Command {
+    /// Print statistics and difficulty metrics for a maze instead of rendering it
+    Stats(StatsArgs),
+    /// Render a maze saved with --save, or a rectangular maze drawn as text, without regenerating it
+    Render(RenderArgs),
+}
 
-    if args.width == 0 || args.height == 0 {
-        eprintln!("Error: Width and height must be greater than 0");
+#[derive(clap::Args)]
+struct RenderArgs {
+    /// Saved maze file (JSON or packed) or text drawing to render
+    #[arg(short, long)]
+    input: String,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(clap::Args)]
+struct StatsArgs {
+    #[command(flatten)]
+    maze: MazeArgs,
+
+    /// Report format: text or json (default: text)
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: StatsFormat,
+}
+
+fn parse_number(s: &str) -> Result<f64, String> {
+    s.parse().map_err(|_| format!("'{}' is not a number", s))
+}
+
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    let value = parse_number(s)?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{} is not between 0.0 and 1.0", value))
+    }
+}
+
+/// Parse a finite number above zero; `what` names the setting in errors
+fn parse_positive(s: &str, what: &str) -> Result<f64, String> {
+    let value = parse_number(s)?;
+    if value > 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(format!("{} is not a positive {}", value, what))
+    }
+}
+
+/// Parse a finite number of zero or more; `what` names the setting in errors
+fn parse_non_negative(s: &str, what: &str) -> Result<f64, String> {
+    let value = parse_number(s)?;
+    if value >= 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(format!("{} is not a valid {}", value, what))
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
         std::process::exit(1);
     }
+}
 
-    println!("Generating {}x{} {:?} maze...", args.width, args.height, args.grid_type);
+fn run() -> std::io::Result<()> {
+    // Plain `maze [OPTIONS]` generates a maze; subcommands are optional extras
+    let cli = Command::augment_subcommands(Args::command())
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true);
+    let matches = cli.get_matches();
 
-    match args.grid_type {
-        GridType::Rectangular => {
-            process_maze::<RectShape>(&args)?;
-        }
-        GridType::Triangular => {
-            process_maze::<TriShape>(&args)?;
-        }
-        GridType::Hexagonal => {
-            process_maze::<HexShape>(&args)?;
+    if matches.subcommand().is_some() {
+        let command = Command::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        return run_command(command);
+    }
+
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    check_size(&args.maze);
+    check_algorithm(&args.maze);
+    check_output(&args.output);
+    // Read the theme before spending time on generation, so a bad file fails fast
+    let theme = args.output.theme()?;
+
+    let mut status = args.output.status();
+    writeln!(status, "Generating {}x{} {} maze...", args.maze.width, args.maze.height, args.maze.grid_type)?;
+    process_maze(&args, &theme, &mut *status)
+}
+
+fn run_command(command: Command) -> std::io::Result<()> {
+    match command {
+        Command::Stats(stats_args) => {
+            check_size(&stats_args.maze);
+            check_algorithm(&stats_args.maze);
+            print_stats(&stats_args)?;
         }
-        GridType::Octagonal => {
-            process_maze::<OctShape>(&args)?;
+        Command::Render(render_args) => {
+            check_output(&render_args.output);
+            let theme = render_args.output.theme()?;
+            let mut status = render_args.output.status();
+            render_saved(&std::fs::read(&render_args.input)?, &render_args.output, &theme, &mut *status)?;
         }
     }
-
     Ok(())
 }
 
-fn process_maze<S: Shape>(args: &Args) -> std::io::Result<()> {
-    let mut maze = GenericMaze::<S>::new(args.width, args.height);
-    if args.debug {
-        S::print_debug_info(&maze);
+fn check_size(args: &MazeArgs) {
+    if args.width == 0 || args.height == 0 {
+        eprintln!("Error: Width and height must be greater than 0");
+        std::process::exit(1);
+    }
+}
+
+fn check_algorithm(args: &MazeArgs) {
+    // The other generators have no settings, so these would be silently ignored
+    if args.algorithm != Algorithm::GrowingTree && (args.difficulty.is_some() || args.selection.is_some()) {
+        eprintln!("Error: --difficulty and --selection only apply to the growing-tree algorithm");
+        std::process::exit(1);
+    }
+}
+
+fn check_output(output: &OutputArgs) {
+    if output.output == "-" && output.format() != OutputFormat::Text {
+        eprintln!("Error: only text output can be written to stdout");
+        std::process::exit(1);
+    }
+
+    // Walls as thick as the tunnels would close them off entirely
+    let tunnel_mm = output.tunnel_width as f64 * 25.4 / output.dpi;
+    if output.format() == OutputFormat::Stl && output.wall_thickness >= tunnel_mm {
+        eprintln!(
+            "Error: wall thickness {} mm must be less than the tunnel width ({:.2} mm at {} DPI)",
+            output.wall_thickness, tunnel_mm, output.dpi
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Generate and write the maze, reporting progress (and the debug dump) to `status`
+fn process_maze(args: &Args, theme: &Theme, status: &mut dyn Write) -> std::io::Result<()> {
+    if args.output.debug {
+        empty_maze(&args.maze).print_debug_info(status)?;
     }
 
     if !args.all_walls {
-        let is_hard = args.difficulty == Difficulty::Hard;
-        maze.generate(is_hard);
+        let (maze, attempts) = build_maze(&args.maze)?;
+        writeln!(status, "Seed: {}", maze.seed().unwrap_or_default())?;
+        if !args.maze.constraints().is_empty() {
+            writeln!(status, "Constraints met after {} attempt(s)", attempts)?;
+        }
         let solution = maze.solve();
-        let svg_content = S::to_svg(&maze, args.tunnel_width, None, args.debug);
-        let svg_solution = S::to_svg(&maze, args.tunnel_width, Some(&solution), args.debug);
-        write_output(&args.output, &svg_content, &svg_solution)?;
+        writeln!(status, "Solution length: {} cells", solution.len())?;
+        render_maze(&maze, Some(&solution), &args.output, theme, status)?;
+
+        if let Some(path) = &args.save {
+            if path.ends_with(".mazb") {
+                std::fs::write(path, maze.to_packed()?)?;
+            } else {
+                maze.to_file(Some(&solution)).save(path)?;
+            }
+            writeln!(status, "Maze data saved to {}", path)?;
+        }
     } else {
         // Render all walls without generating maze
-        let svg_content = S::to_svg(&maze, args.tunnel_width, None, args.debug);
-        write_output(&args.output, &svg_content, &svg_content)?;
+        render_maze(&empty_maze(&args.maze), None, &args.output, theme, status)?;
     }
 
     Ok(())
 }
 
-fn write_output(output_path: &str, svg_content: &str, svg_solution: &str) -> std::io::Result<()> {
+fn render_saved(data: &[u8], output: &OutputArgs, theme: &Theme, status: &mut dyn Write) -> std::io::Result<()> {
+    let (maze, solution) = AnyMaze::load(data)?;
+    let solution = solution.unwrap_or_else(|| maze.solve());
+    render_maze(&maze, Some(&solution), output, theme, status)
+}
+
+/// Write the maze and its solution (or the bare maze twice, if there is none)
+fn render_maze(
+    maze: &AnyMaze,
+    solution: Option<&[usize]>,
+    output: &OutputArgs,
+    theme: &Theme,
+    status: &mut dyn Write,
+) -> std::io::Result<()> {
+    let format = output.format();
+    let pdf = |pages: &[PdfPage]| write_pdf(pages, output.page, mm_to_points(output.margin), maze.seed());
+    let pdf_page = |path: Option<&[usize]>| maze.pdf_page(output.tunnel_width, path, output.debug, theme);
+
+    if format == OutputFormat::Pdf && output.solution_page {
+        let document = pdf(&[pdf_page(None), pdf_page(solution)]);
+        File::create(&output.output)?.write_all(&document)?;
+        return writeln!(status, "Maze and solution saved to {}", output.output);
+    }
+
+    let render = |path: Option<&[usize]>| -> std::io::Result<Vec<u8>> {
+        Ok(match format {
+            OutputFormat::Svg => maze.to_svg(output.tunnel_width, path, output.debug, theme).into_bytes(),
+            OutputFormat::Png => maze.to_png(output.tunnel_width, path, output.debug, theme, output.dpi)?,
+            OutputFormat::Pdf => pdf(&[pdf_page(path)]),
+            OutputFormat::Plot => maze.to_plot(output.tunnel_width, path).to_svg(maze.seed(), theme).into_bytes(),
+            OutputFormat::Gcode => {
+                let plot = maze.to_plot(output.tunnel_width, path);
+                match path {
+                    Some(_) => plot.solution_gcode(&output.gcode_options(), maze.seed(), output.separate_solution),
+                    None => plot.to_gcode(&output.gcode_options(), maze.seed()),
+                }
+                .into_bytes()
+            }
+            OutputFormat::Dxf => maze.to_dxf(output.tunnel_width, path, output.debug, output.dpi).into_bytes(),
+            OutputFormat::Stl => maze.to_stl(output.tunnel_width, path, output.stl_options()),
+            OutputFormat::Text => maze.to_text(path, output.text_style).into_bytes(),
+        })
+    };
+    let content = render(None)?;
+    let solution_content = match solution {
+        Some(path) => render(Some(path))?,
+        None => content.clone(),
+    };
+    write_output(&output.output, format.extension(), &content, &solution_content, status)
+}
+
+fn print_stats(args: &StatsArgs) -> std::io::Result<()> {
+    let (maze, attempts) = build_maze(&args.maze)?;
+    if !args.maze.constraints().is_empty() {
+        eprintln!("Constraints met after {} attempt(s)", attempts);
+    }
+    let stats = maze.stats(&maze.solve());
+    match args.format {
+        StatsFormat::Text => print!("{}", stats.to_text()),
+        StatsFormat::Json => println!("{}", stats.to_json()),
+    }
+    Ok(())
+}
+
+/// Generate the maze the options describe (see `maze::generate`). Returns
+/// the maze and the number of attempts taken to meet the constraints.
+fn build_maze(args: &MazeArgs) -> std::io::Result<(AnyMaze, usize)> {
+    generate(&args.options()).map_err(std::io::Error::other)
+}
+
+/// A maze of the requested shape and size with every wall standing
+fn empty_maze(args: &MazeArgs) -> AnyMaze {
+    // Clap only accepts registered grid types
+    AnyMaze::new(&args.grid_type, args.width, args.height).expect("grid type is registered")
+}
+
+fn write_output(output_path: &str, extension: &str, content: &[u8], solution: &[u8], status: &mut dyn Write) -> std::io::Result<()> {
+    if output_path == "-" {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(content)?;
+        stdout.write_all(b"\n")?;
+        return stdout.write_all(solution);
+    }
+
     let mut file = File::create(output_path)?;
-    file.write_all(svg_content.as_bytes())?;
-    println!("Maze saved to {}", output_path);
+    file.write_all(content)?;
+    writeln!(status, "Maze saved to {}", output_path)?;
 
-    let solution_filename = if output_path.ends_with(".svg") {
-        output_path.replace(".svg", "_solution.svg")
-    } else {
-        format!("{}_solution.svg", output_path)
+    let suffix = format!(".{}", extension);
+    let solution_filename = match output_path.strip_suffix(&suffix) {
+        Some(stem) => format!("{}_solution{}", stem, suffix),
+        None => format!("{}_solution{}", output_path, suffix),
     };
 
     let mut solution_file = File::create(&solution_filename)?;
-    solution_file.write_all(svg_solution.as_bytes())?;
-    println!("Solution saved to {}", solution_filename);
+    solution_file.write_all(solution)?;
+    writeln!(status, "Solution saved to {}", solution_filename)?;
 
     Ok(())
 }
@@ -134,7 +677,7 @@ mod tests {
 
     #[test]
     fn test_all_shapes_all_modes() -> std::io::Result<()> {
-        // Test cases: 4 shapes × 3 modes (debug, normal easy, normal hard) = 12 test cases
+        // Test cases: 5 shapes × 3 modes (debug, normal easy, normal hard) = 15 test cases
         let test_cases = vec![
             ("rectangular", "debug", 5, 5, true, Difficulty::Easy),
             ("rectangular", "normal", 75, 75, false, Difficulty::Easy),
@@ -148,6 +691,9 @@ mod tests {
             ("octagonal", "debug", 5, 5, true, Difficulty::Easy),
             ("octagonal", "normal", 75, 75, false, Difficulty::Easy),
             ("octagonal", "normal_hard", 75, 75, false, Difficulty::Hard),
+            ("polar", "debug", 5, 5, true, Difficulty::Easy),
+            ("polar", "normal", 75, 75, false, Difficulty::Easy),
+            ("polar", "normal_hard", 75, 75, false, Difficulty::Hard),
         ];
 
         for (shape_name, mode, width, height, debug, difficulty) in test_cases {
@@ -159,28 +705,60 @@ mod tests {
             let tunnel_width = if debug { 60 } else { 20 };
 
             let args = Args {
-                width,
-                height,
-                output: output_file.clone(),
-                tunnel_width,
-                grid_type: match shape_name {
-                    "rectangular" => GridType::Rectangular,
-                    "triangular" => GridType::Triangular,
-                    "hexagonal" => GridType::Hexagonal,
-                    "octagonal" => GridType::Octagonal,
-                    _ => panic!("Unknown shape"),
+                maze: MazeArgs {
+                    width,
+                    height,
+                    grid_type: shape_name.to_string(),
+                    difficulty: Some(difficulty),
+                    seed: None,
+                    algorithm: Algorithm::GrowingTree,
+                    selection: None,
+                    braid: 0.0,
+                    entrance: None,
+                    exit: None,
+                    longest_path: false,
+                    min_solution_ratio: None,
+                    max_solution_ratio: None,
+                    min_dead_ends: None,
+                    max_dead_ends: None,
+                    max_attempts: 1000,
+                },
+                output: OutputArgs {
+                    output: output_file.clone(),
+                    format: None,
+                    tunnel_width,
+                    dpi: 96.0,
+                    page: PageSize::LETTER,
+                    margin: 10.0,
+                    solution_page: false,
+                    units: Units::Millimetres,
+                    origin: Origin::BottomLeft,
+                    feed_rate: 1000.0,
+                    pen_up: PenCommand::Z(5.0),
+                    pen_down: PenCommand::Z(0.0),
+                    separate_solution: false,
+                    wall_height: 10.0,
+                    wall_thickness: 1.5,
+                    base_thickness: 2.0,
+                    text_style: TextStyle::Ascii,
+                    theme: None,
+                    background: None,
+                    wall_color: None,
+                    wall_width: None,
+                    solution_color: None,
+                    solution_width: None,
+                    solution_dash: None,
+                    label_color: None,
+                    label_font: None,
+                    cell_fill: None,
+                    heatmap: false,
+                    debug,
                 },
-                debug,
                 all_walls: false,
-                difficulty,
+                save: None,
             };
 
-            match args.grid_type {
-                GridType::Rectangular => process_maze::<RectShape>(&args)?,
-                GridType::Triangular => process_maze::<TriShape>(&args)?,
-                GridType::Hexagonal => process_maze::<HexShape>(&args)?,
-                GridType::Octagonal => process_maze::<OctShape>(&args)?,
-            }
+            process_maze(&args, &Theme::default(), &mut std::io::sink())?;
 
             // Verify the files were created
             assert!(fs::metadata(&output_file).is_ok(), "Main SVG file should exist");
@@ -189,7 +767,63 @@ mod tests {
             assert!(fs::metadata(&solution_file).is_ok(), "Solution SVG file should exist");
         }
 
-        println!("\nAll 12 test cases completed successfully!");
+        println!("\nAll 15 test cases completed successfully!");
         Ok(())
     }
+
+    #[test]
+    fn test_maze_args() {
+        // Difficulty picks the growing-tree mix unless one is given
+        let options = Args::parse_from(["maze", "-W", "9", "-H", "7", "-o", "-", "-D", "hard", "-s", "3"]).maze.options();
+        assert_eq!(options.algorithm, maze::Algorithm::GrowingTree(GrowingTree::for_difficulty(true)));
+        assert_eq!((options.grid_type.as_str(), options.width, options.height, options.seed), ("rectangular", 9, 7, Some(3)));
+        let options = Args::parse_from(["maze", "-W", "9", "-H", "7", "-o", "-", "--selection", "oldest"]).maze.options();
+        assert_eq!(options.algorithm, maze::Algorithm::GrowingTree("oldest".parse().unwrap()));
+        let options = Args::parse_from(["maze", "-W", "9", "-H", "7", "-o", "-", "-a", "wilson", "--max-dead-ends", "40"]).maze.options();
+        assert_eq!(options.algorithm, maze::Algorithm::Wilson);
+        assert_eq!(options.constraints, Constraints { max_dead_ends: Some(40), ..Constraints::default() });
+
+        // Library errors come back as errors, not exits
+        let args = Args::parse_from(["maze", "-W", "0", "-H", "7", "-o", "-"]);
+        assert!(build_maze(&args.maze).is_err());
+
+        // There is no generated maze to save when only the walls are drawn
+        assert!(Args::try_parse_from(["maze", "-W", "5", "-H", "5", "-o", "-", "--all-walls", "--save", "s.json"]).is_err());
+        assert!(Args::try_parse_from(["maze", "-W", "5", "-H", "5", "-o", "-", "--save", "s.json"]).is_ok());
+    }
+
+    #[test]
+    fn test_output_args() {
+        let format = |output: &str| Args::parse_from(["maze", "-W", "3", "-H", "2", "-o", output]).output.format();
+        assert_eq!(format("maze.PNG"), OutputFormat::Png);
+        assert_eq!(format("maze.pdf"), OutputFormat::Pdf);
+        assert_eq!(format("-"), OutputFormat::Text);
+        assert_eq!(format("board.gcode"), OutputFormat::Gcode);
+        assert_eq!(format("cut.DXF"), OutputFormat::Dxf);
+        assert_eq!(format("maze.stl"), OutputFormat::Stl);
+
+        let args = Args::parse_from(["maze", "-W", "8", "-H", "6", "-o", "board.gcode", "--pen-down", "-1.5"]);
+        let options = args.output.gcode_options();
+        assert_eq!((options.pen_up, options.pen_down), (PenCommand::Z(5.0), PenCommand::Z(-1.5)));
+        assert_eq!((options.units, options.origin), (Units::Millimetres, Origin::BottomLeft));
+        let options = args.output.stl_options();
+        assert_eq!((options.wall_height, options.wall_thickness, options.base_thickness), (10.0, 1.5, 2.0));
+
+        let args = Args::parse_from(["maze", "-W", "12", "-H", "9", "-o", "maze.svg", "--heatmap"]);
+        assert_eq!(args.output.theme().unwrap().cell_fill, CellFill::Heatmap);
+
+        // Flags override the theme file
+        let path = std::env::temp_dir().join("maze_test_theme.json");
+        fs::write(&path, r#"{"wall_color": "green", "wall_width": 5, "background": "silver"}"#).unwrap();
+        let args = Args::parse_from([
+            "maze", "-W", "8", "-H", "6", "-s", "2", "-o", "maze.svg",
+            "--theme", path.to_str().unwrap(), "--wall-width", "1.5", "--solution-dash", "2,3", "--label-font", "serif",
+        ]);
+        let theme = args.output.theme().unwrap();
+        fs::remove_file(&path).unwrap();
+        let error = args.output.theme().unwrap_err().to_string();
+        assert!(error.starts_with(&format!("could not load theme {}", path.display())), "{}", error);
+        assert_eq!((theme.wall_color, theme.wall_width, theme.background), (Color([0, 128, 0]), 1.5, Color([192, 192, 192])));
+        assert_eq!((theme.solution_dash, theme.label_font), (vec![2.0, 3.0], "serif".to_string()));
+    }
 }
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+
+use crate::genericmaze::{GenericMaze, Shape};
+use crate::{packed, textmaze};
+
+/// Current version of the saved maze format. Bump when the layout changes.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// A maze as stored on disk: everything needed to re-render it without
+/// regenerating. `walls` holds each cell's walls in the shape's neighbor order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MazeFile {
+    pub version: u32,
+    pub grid_type: String,
+    pub width: usize,
+    pub height: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub entrance: usize,
+    pub exit: usize,
+    /// Boundary wall opened at the entrance, if not the shape's preferred one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entrance_wall: Option<usize>,
+    /// Boundary wall opened at the exit, if not the shape's preferred one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_wall: Option<usize>,
+    pub walls: Vec<Vec<bool>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Vec<usize>>,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl MazeFile {
+    /// Capture a maze (and optionally its solution) for saving
+    pub fn from_maze<S: Shape>(maze: &GenericMaze<S>, solution: Option<&[usize]>) -> Self {
+        MazeFile {
+            version: FORMAT_VERSION,
+            grid_type: S::name().to_string(),
+            width: maze.width,
+            height: maze.height,
+            seed: maze.seed,
+            entrance: maze.entrance,
+            exit: maze.exit,
+            entrance_wall: maze.entrance_wall,
+            exit_wall: maze.exit_wall,
+            walls: maze.cells.iter().map(|cell| cell.walls.clone()).collect(),
+            solution: solution.map(|path| path.to_vec()),
+        }
+    }
+
+    /// Rebuild the maze, checking that the file really describes a maze of shape `S`
+    pub fn to_maze<S: Shape>(&self) -> io::Result<GenericMaze<S>> {
+        if self.grid_type != S::name() {
+            return Err(invalid(format!("saved maze is {}, not {}", self.grid_type, S::name())));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(invalid("width and height must be greater than 0".to_string()));
+        }
+
+        // Check the size against the walls actually present before allocating
+        match GenericMaze::<S>::cell_count(self.width, self.height, self.walls.len()) {
+            Some(count) if count == self.walls.len() => {}
+            Some(count) => return Err(invalid(format!("expected walls for {} cells, found {}", count, self.walls.len()))),
+            None => return Err(invalid(format!("found walls for {} cells, too few for a {}x{} maze", self.walls.len(), self.width, self.height))),
+        }
+
+        let mut maze = GenericMaze::<S>::new(self.width, self.height);
+        for (idx, (cell, walls)) in maze.cells.iter_mut().zip(&self.walls).enumerate() {
+            // Version 1 gave every cell of a shape as many walls as its largest
+            // cells, padding the rest (such as octagonal squares) with unused
+            // walls that were always standing
+            let walls = match walls.split_at_checked(cell.walls.len()) {
+                Some((walls, padding)) if self.version == 1 && padding.iter().all(|&wall| wall) => walls,
+                _ => walls,
+            };
+            if walls.len() != cell.walls.len() {
+                return Err(invalid(format!("cell {} should have {} walls, found {}", idx, cell.walls.len(), walls.len())));
+            }
+            cell.walls = walls.to_vec();
+        }
+        maze.check_walls().map_err(invalid)?;
+
+        let num_cells = maze.cells.len();
+        if self.entrance >= num_cells || self.exit >= num_cells {
+            return Err(invalid("entrance or exit is outside the maze".to_string()));
+        }
+        for (name, idx, wall) in [("entrance", self.entrance, self.entrance_wall), ("exit", self.exit, self.exit_wall)] {
+            if let Some(wall) = wall
+                && !S::boundary_walls(&maze, idx).contains(&wall)
+            {
+                return Err(invalid(format!("{} wall {} is not on the outline of the maze", name, wall)));
+            }
+        }
+        if let Some(solution) = &self.solution
+            && solution.iter().any(|&idx| idx >= num_cells)
+        {
+            return Err(invalid("solution refers to a cell outside the maze".to_string()));
+        }
+        if let Some(solution) = &self.solution
+            && !solution.is_empty()
+        {
+            if solution[0] != self.entrance || solution[solution.len() - 1] != self.exit {
+                return Err(invalid("solution does not run from the entrance to the exit".to_string()));
+            }
+            if !solution.windows(2).all(|pair| maze.passages_of(pair[0]).any(|(_, next)| next == pair[1])) {
+                return Err(invalid("solution passes through a wall".to_string()));
+            }
+        }
+
+        maze.seed = self.seed;
+        maze.entrance = self.entrance;
+        maze.exit = self.exit;
+        maze.entrance_wall = self.entrance_wall;
+        maze.exit_wall = self.exit_wall;
+        Ok(maze)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("maze files are always serializable")
+    }
+
+    /// Parse a saved maze, rejecting format versions this build does not know
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let file: MazeFile = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        if file.version == 0 || file.version > FORMAT_VERSION {
+            return Err(invalid(format!(
+                "unsupported maze format version {} (this build reads up to {})",
+                file.version, FORMAT_VERSION
+            )));
+        }
+        Ok(file)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+/// Read a saved JSON maze, or a rectangular maze drawn as text
+fn read_maze_file(data: &[u8]) -> io::Result<MazeFile> {
+    let text = String::from_utf8_lossy(data);
+    if text.trim_start().starts_with('{') {
+        MazeFile::from_json(&text)
+    } else {
+        textmaze::parse(&text)
+    }
+}
+
+/// Shape name of a saved maze: JSON, packed binary or a text drawing
+pub fn saved_grid_type(data: &[u8]) -> io::Result<String> {
+    if packed::is_packed(data) {
+        packed::packed_grid_type(data)
+    } else {
+        Ok(read_maze_file(data)?.grid_type)
+    }
+}
+
+/// Load a saved maze in any of those formats, along with its solution if one was stored
+pub fn load_saved<S: Shape>(data: &[u8]) -> io::Result<(GenericMaze<S>, Option<Vec<usize>>)> {
+    if packed::is_packed(data) {
+        Ok((packed::from_packed(data)?, None))
+    } else {
+        let file = read_maze_file(data)?;
+        Ok((file.to_maze()?, file.solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::Kruskal;
+    use crate::genericmaze::seeded_rng;
+    use crate::render::Theme;
+    use crate::shapes::{OctShape, PolarShape, RectShape};
+    use crate::testutil::{build, options, shape_tests};
+
+    fn test_save_and_load_round_trip<S: Shape>() {
+        let mut options = options::<S>(7, 6, 13);
+        options.braid = 0.5;
+        options.longest_path = true;
+        let maze = build::<S>(&options);
+        let solution = maze.solve();
+
+        let file = MazeFile::from_json(&MazeFile::from_maze(&maze, Some(&solution)).to_json()).unwrap();
+        let loaded = file.to_maze::<S>().unwrap();
+        assert_eq!(file.solution.as_deref(), Some(&solution[..]));
+        assert_eq!((loaded.seed, loaded.entrance, loaded.exit), (maze.seed, maze.entrance, maze.exit));
+        assert_eq!(S::to_svg(&loaded, 20, Some(&solution), false, &Theme::default()), S::to_svg(&maze, 20, Some(&solution), false, &Theme::default()));
+    }
+
+    shape_tests!(test_save_and_load_round_trip);
+
+    #[test]
+    fn test_load_rejects_bad_files() {
+        let mut maze = GenericMaze::<RectShape>::new(3, 3);
+        maze.generate_with_rng(&Kruskal, &mut seeded_rng(1));
+        let good = MazeFile::from_maze(&maze, None);
+
+        let mut wrong_shape = good.clone();
+        wrong_shape.grid_type = "hexagonal".to_string();
+        assert!(wrong_shape.to_maze::<RectShape>().is_err());
+
+        let mut one_sided = good.clone();
+        one_sided.walls[0][2] = !one_sided.walls[0][2];
+        assert!(one_sided.to_maze::<RectShape>().is_err());
+
+        let mut future = good.clone();
+        future.version = FORMAT_VERSION + 1;
+        assert!(MazeFile::from_json(&future.to_json()).is_err());
+
+        // Sizes are checked against the walls present before allocating
+        let mut huge = good.clone();
+        (huge.width, huge.height, huge.walls) = (300_000, 300_000, Vec::new());
+        assert!(huge.to_maze::<RectShape>().is_err());
+        huge.grid_type = "polar".to_string();
+        assert!(huge.to_maze::<PolarShape>().is_err());
+
+        // A stored solution must follow open passages from entrance to exit
+        let mut solved = good.clone();
+        solved.solution = Some(maze.solve());
+        assert!(solved.to_maze::<RectShape>().is_ok());
+        solved.solution = Some(vec![maze.entrance, maze.exit]);
+        assert!(solved.to_maze::<RectShape>().is_err());
+        solved.solution = Some(maze.solve()[1..].to_vec());
+        assert!(solved.to_maze::<RectShape>().is_err());
+
+        assert!(good.to_maze::<RectShape>().is_ok());
+
+        // Version 1 padded octagonal squares to eight walls
+        let mut octagonal = GenericMaze::<OctShape>::new(3, 3);
+        octagonal.generate_with_rng(&Kruskal, &mut seeded_rng(1));
+        let mut old = MazeFile::from_maze(&octagonal, None);
+        old.version = 1;
+        for walls in old.walls.iter_mut().filter(|walls| walls.len() == 4) {
+            walls.extend([true; 4]);
+        }
+        let loaded = old.to_maze::<OctShape>().unwrap();
+        assert!((0..9).all(|idx| loaded.cells[idx].walls == octagonal.cells[idx].walls));
+        old.version = 2;
+        assert!(old.to_maze::<OctShape>().is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use std::fs;
+use std::io;
+
+use crate::genericmaze::{GenericMaze, Shape};
+
+// Compact binary maze format for archiving large numbers of (large) mazes.
+//
+// Only walls between two cells are stored, one bit per shared edge; outer
+// walls are implied by the shape. Layout (integers little-endian):
+//
+// ```text
+// magic     4 bytes  "MAZB"
+// version   u8
+// name_len  u8, then the shape name (ASCII)
+// width     u32
+// height    u32
+// flags     u8       bit 0: seed present
+// seed      u64      only if flagged
+// entrance  u32
+// exit      u32
+// edges     one bit per shared edge, LSB first, 1 = wall present
+// crc32     u32      over everything above
+// ```
+//
+// Edges are listed cell by cell in neighbor order, counting each edge only
+// from its lower-numbered cell.
+
+/// Magic number at the start of every packed maze
+pub const MAGIC: &[u8; 4] = b"MAZB";
+/// Current version of the packed format. Bump when the layout changes.
+pub const PACKED_VERSION: u8 = 1;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// A header field as a u32, or an error if the maze is too large for the format
+fn field(value: usize, name: &str) -> io::Result<[u8; 4]> {
+    u32::try_from(value)
+        .map(u32::to_le_bytes)
+        .map_err(|_| invalid(format!("{} {} is too large for the packed format", name, value)))
+}
+
+/// Serialize a maze to the packed format. Fails if a size or endpoint does
+/// not fit its 32-bit header field, or an endpoint opens on a wall other than
+/// the shape's preferred one, which the format cannot record.
+pub fn to_packed<S: Shape>(maze: &GenericMaze<S>) -> io::Result<Vec<u8>> {
+    for (name, idx, wall) in [("entrance", maze.entrance, maze.entrance_wall), ("exit", maze.exit, maze.exit_wall)] {
+        if wall.is_some_and(|wall| Some(wall) != maze.opening_wall(idx)) {
+            return Err(invalid(format!("the packed format cannot record which side the {} opens on", name)));
+        }
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(PACKED_VERSION);
+    out.push(S::name().len() as u8);
+    out.extend_from_slice(S::name().as_bytes());
+    out.extend_from_slice(&field(maze.width, "width")?);
+    out.extend_from_slice(&field(maze.height, "height")?);
+    match maze.seed {
+        Some(seed) => {
+            out.push(1);
+            out.extend_from_slice(&seed.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+    out.extend_from_slice(&field(maze.entrance, "entrance")?);
+    out.extend_from_slice(&field(maze.exit, "exit")?);
+
+    let mut byte = 0u8;
+    let mut bit = 0;
+    for idx in 0..maze.cells.len() {
+        for (edge_idx, neighbor) in maze.neighbors_of(idx) {
+            if neighbor < idx {
+                continue;
+            }
+            if maze.cells[idx].walls[edge_idx] {
+                byte |= 1 << bit;
+            }
+            bit += 1;
+            if bit == 8 {
+                out.push(byte);
+                byte = 0;
+                bit = 0;
+            }
+        }
+    }
+    if bit > 0 {
+        out.push(byte);
+    }
+
+    let checksum = crc32(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    Ok(out)
+}
+
+/// Reads the header fields in order, failing cleanly on truncated input
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos + n).ok_or_else(|| invalid("packed maze is truncated"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<usize> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Check magic, version and checksum, and return the shape name and a
+/// reader positioned just after it
+fn open_packed(data: &[u8]) -> io::Result<(String, Reader<'_>)> {
+    if !is_packed(data) {
+        return Err(invalid("not a packed maze file"));
+    }
+    if data.len() < MAGIC.len() + 4 {
+        return Err(invalid("packed maze is truncated"));
+    }
+    let (body, checksum) = data.split_at(data.len() - 4);
+    if crc32(body) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err(invalid("packed maze checksum mismatch"));
+    }
+
+    let mut reader = Reader { data: body, pos: MAGIC.len() };
+    let version = reader.u8()?;
+    if version == 0 || version > PACKED_VERSION {
+        return Err(invalid(format!(
+            "unsupported packed maze version {} (this build reads up to {})",
+            version, PACKED_VERSION
+        )));
+    }
+    let name_len = reader.u8()? as usize;
+    let name = String::from_utf8(reader.take(name_len)?.to_vec()).map_err(|_| invalid("shape name is not valid text"))?;
+    Ok((name, reader))
+}
+
+/// True if the data starts with the packed format's magic number
+pub fn is_packed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// The shape name recorded in a packed maze
+pub fn packed_grid_type(data: &[u8]) -> io::Result<String> {
+    open_packed(data).map(|(name, _)| name)
+}
+
+/// Deserialize a packed maze of shape `S`
+pub fn from_packed<S: Shape>(data: &[u8]) -> io::Result<GenericMaze<S>> {
+    let (name, mut reader) = open_packed(data)?;
+    if name != S::name() {
+        return Err(invalid(format!("saved maze is {}, not {}", name, S::name())));
+    }
+
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    if width == 0 || height == 0 {
+        return Err(invalid("width and height must be greater than 0"));
+    }
+    let seed = if reader.u8()? & 1 != 0 { Some(reader.u64()?) } else { None };
+    let entrance = reader.u32()?;
+    let exit = reader.u32()?;
+
+    // The cells are connected, so there are at least one fewer shared edges
+    // than cells; check the size against the payload before allocating
+    let bits = &reader.data[reader.pos..];
+    let num_cells = GenericMaze::<S>::cell_count(width, height, bits.len().saturating_mul(8).saturating_add(1))
+        .ok_or_else(|| invalid("packed maze is truncated"))?;
+    if entrance >= num_cells || exit >= num_cells {
+        return Err(invalid("entrance or exit is outside the maze"));
+    }
+
+    let mut maze = GenericMaze::<S>::new(width, height);
+    let mut bit = 0;
+    for idx in 0..maze.cells.len() {
+        let edges: Vec<(usize, usize)> = maze.neighbors_of(idx).filter(|&(_, n)| n > idx).collect();
+        for (edge_idx, _) in edges {
+            let byte = *bits.get(bit / 8).ok_or_else(|| invalid("packed maze is truncated"))?;
+            if byte & (1 << (bit % 8)) == 0 {
+                maze.carve(idx, edge_idx);
+            }
+            bit += 1;
+        }
+    }
+    if bits.len() != bit.div_ceil(8) {
+        return Err(invalid("packed maze has trailing data"));
+    }
+
+    maze.seed = seed;
+    maze.entrance = entrance;
+    maze.exit = exit;
+    Ok(maze)
+}
+
+pub fn save_packed<S: Shape>(maze: &GenericMaze<S>, path: &str) -> io::Result<()> {
+    fs::write(path, to_packed(maze)?)
+}
+
+/// CRC-32 (IEEE 802.3, as used by zip and PNG)
+pub fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut c = i as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+                k += 1;
+            }
+            table[i] = c;
+            i += 1;
+        }
+        table
+    };
+
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc = TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFF_FFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoints::Endpoint;
+    use crate::mazefile::load_saved;
+    use crate::testutil::{build, options, shape_tests};
+
+    fn packed_maze<S: Shape>() -> GenericMaze<S> {
+        let mut options = options::<S>(13, 11, 17);
+        options.braid = 0.3;
+        options.exit = Some(Endpoint::Farthest);
+        build::<S>(&options)
+    }
+
+    fn test_packed_round_trip<S: Shape>() {
+        let maze = packed_maze::<S>();
+        let data = to_packed(&maze).unwrap();
+        assert_eq!(packed_grid_type(&data).unwrap(), S::name());
+        let (loaded, solution) = load_saved::<S>(&data).unwrap();
+        assert!(solution.is_none());
+        assert_eq!((loaded.seed, loaded.entrance, loaded.exit), (maze.seed, maze.entrance, maze.exit));
+        assert!((0..maze.cells.len()).all(|idx| maze.cells[idx].walls == loaded.cells[idx].walls));
+    }
+
+    fn test_packed_checksum_catches_flipped_bits<S: Shape>() {
+        let mut corrupt = to_packed(&packed_maze::<S>()).unwrap();
+        let middle = corrupt.len() / 2;
+        corrupt[middle] ^= 0x10;
+        assert!(from_packed::<S>(&corrupt).is_err());
+    }
+
+    fn test_packed_rejects_huge_sizes<S: Shape>() {
+        // A huge size with a valid checksum is rejected before allocating
+        let data = to_packed(&packed_maze::<S>()).unwrap();
+        let mut huge = data[..data.len() - 4].to_vec();
+        let size = 6 + S::name().len();
+        huge[size..size + 8].copy_from_slice(&[0xE0, 0x93, 0x04, 0x00, 0xE0, 0x93, 0x04, 0x00]);
+        huge.extend_from_slice(&crc32(&huge).to_le_bytes());
+        assert!(from_packed::<S>(&huge).is_err());
+    }
+
+    fn test_packed_rejects_sizes_over_u32<S: Shape>() {
+        // Sizes that do not fit the header are an error, not truncated
+        let mut wide = GenericMaze::<S>::new(1, 1);
+        wide.width = 1 << 32;
+        assert!(to_packed(&wide).is_err());
+    }
+
+    shape_tests!(
+        test_packed_round_trip,
+        test_packed_checksum_catches_flipped_bits,
+        test_packed_rejects_huge_sizes,
+        test_packed_rejects_sizes_over_u32,
+    );
+}
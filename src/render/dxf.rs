@@ -0,0 +1,254 @@
+// DXF output for CAD and CAM software. Walls are merged into polylines as
+// for plotting, in millimetres with y pointing up, and kept on separate
+// layers so the outer border, inner walls, solution and labels can be given
+// different operations on import.
+use std::fmt::Display;
+
+use super::plot::strokes;
+use super::{decimal, Painter, Point};
+
+/// Layer names and their ACI colors: black/white, blue, red, green
+const LAYERS: [(&str, u8); 4] = [("walls", 7), ("border", 5), ("solution", 1), ("labels", 3)];
+
+/// Painter producing an AutoCAD R12 (AC1009) DXF drawing
+pub struct DxfPainter {
+    /// Millimetres per drawing unit
+    scale: f64,
+    width: f64,
+    height: f64,
+    walls: Vec<(Point, Point)>,
+    border: Vec<(Point, Point)>,
+    labels: Vec<(Point, f64, String)>,
+    solution: Vec<Point>,
+}
+
+impl DxfPainter {
+    /// Create a painter converting drawing units to millimetres at the given
+    /// number of units per inch
+    pub fn new(dpi: f64) -> Self {
+        DxfPainter {
+            scale: 25.4 / dpi,
+            width: 0.0,
+            height: 0.0,
+            walls: Vec::new(),
+            border: Vec::new(),
+            labels: Vec::new(),
+            solution: Vec::new(),
+        }
+    }
+
+    /// Convert to millimetres with y pointing up
+    fn mm(&self, (x, y): Point) -> Point {
+        (x * self.scale, (self.height - y) * self.scale)
+    }
+
+    pub fn finish(self) -> String {
+        let mut dxf = DxfWriter::default();
+        dxf.section("HEADER", |header| {
+            header.pair(9, "$ACADVER");
+            header.pair(1, "AC1009");
+            header.pair(9, "$EXTMIN");
+            header.point(10, (0.0, 0.0));
+            header.pair(9, "$EXTMAX");
+            header.point(10, (self.width * self.scale, self.height * self.scale));
+        });
+        dxf.section("TABLES", |tables| {
+            tables.table("LTYPE", 1, |table| {
+                table.pair(0, "LTYPE");
+                table.pair(2, "CONTINUOUS");
+                table.pair(70, 0);
+                table.pair(3, "Solid line");
+                table.pair(72, 65);
+                table.pair(73, 0);
+                table.pair(40, 0);
+            });
+            tables.table("LAYER", LAYERS.len(), |table| {
+                for (name, color) in LAYERS {
+                    table.pair(0, "LAYER");
+                    table.pair(2, name);
+                    table.pair(70, 0);
+                    table.pair(62, color);
+                    table.pair(6, "CONTINUOUS");
+                }
+            });
+        });
+        dxf.section("ENTITIES", |entities| {
+            for (layer, segments) in [("border", &self.border), ("walls", &self.walls)] {
+                for path in strokes(segments) {
+                    let path: Vec<Point> = path.into_iter().map(|p| self.mm(p)).collect();
+                    entities.polyline(layer, &path);
+                }
+            }
+            if self.solution.len() > 1 {
+                let path: Vec<Point> = self.solution.iter().map(|&p| self.mm(p)).collect();
+                entities.polyline("solution", &path);
+            }
+            for (at, font_size, text) in &self.labels {
+                entities.text(self.mm(*at), font_size * self.scale, text);
+            }
+        });
+        dxf.pair(0, "EOF");
+        dxf.text
+    }
+}
+
+impl Painter for DxfPainter {
+    fn begin(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.walls.push((from, to));
+    }
+
+    fn border(&mut self, from: Point, to: Point) {
+        self.border.push((from, to));
+    }
+
+    fn label(&mut self, at: Point, font_size: f64, text: &str) {
+        self.labels.push((at, font_size, text.to_string()));
+    }
+
+    fn solution(&mut self, points: &[Point]) {
+        self.solution = points.to_vec();
+    }
+}
+
+/// Writes DXF group code / value pairs
+#[derive(Default)]
+struct DxfWriter {
+    text: String,
+}
+
+impl DxfWriter {
+    fn pair(&mut self, code: u16, value: impl Display) {
+        self.text.push_str(&format!("{:>3}\n{}\n", code, value));
+    }
+
+    /// A 3D point at z = 0, using codes `code`, `code + 10` and `code + 20`
+    fn point(&mut self, code: u16, (x, y): Point) {
+        self.pair(code, decimal(x, 3));
+        self.pair(code + 10, decimal(y, 3));
+        self.pair(code + 20, 0);
+    }
+
+    fn section(&mut self, name: &str, contents: impl FnOnce(&mut Self)) {
+        self.pair(0, "SECTION");
+        self.pair(2, name);
+        contents(self);
+        self.pair(0, "ENDSEC");
+    }
+
+    fn table(&mut self, name: &str, entries: usize, contents: impl FnOnce(&mut Self)) {
+        self.pair(0, "TABLE");
+        self.pair(2, name);
+        self.pair(70, entries);
+        contents(self);
+        self.pair(0, "ENDTAB");
+    }
+
+    /// A LINE for a single segment, otherwise a POLYLINE of VERTEX entities,
+    /// closed if it ends where it started
+    fn polyline(&mut self, layer: &str, points: &[Point]) {
+        if let [from, to] = points {
+            self.pair(0, "LINE");
+            self.pair(8, layer);
+            self.point(10, *from);
+            self.point(11, *to);
+            return;
+        }
+
+        let closed = points.len() > 3 && points[0] == points[points.len() - 1];
+        let points = if closed { &points[..points.len() - 1] } else { points };
+        self.pair(0, "POLYLINE");
+        self.pair(8, layer);
+        // Vertices follow; the polyline's own point is always the origin
+        self.pair(66, 1);
+        self.point(10, (0.0, 0.0));
+        self.pair(70, if closed { 1 } else { 0 });
+        for &point in points {
+            self.pair(0, "VERTEX");
+            self.pair(8, layer);
+            self.point(10, point);
+        }
+        self.pair(0, "SEQEND");
+        self.pair(8, layer);
+    }
+
+    /// Single-line text centered on a point
+    fn text(&mut self, at: Point, height: f64, text: &str) {
+        self.pair(0, "TEXT");
+        self.pair(8, "labels");
+        self.point(10, at);
+        self.pair(40, decimal(height, 3));
+        self.pair(1, text);
+        self.pair(72, 1);
+        self.point(11, at);
+        self.pair(73, 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::genericmaze::{GenericMaze, Shape};
+    use crate::testutil::{build, options, shape_tests};
+
+    /// A maze with its solution and debug labels, in DXF at 96 DPI
+    fn dxf<S: Shape>() -> (GenericMaze<S>, String) {
+        let maze = build::<S>(&options::<S>(7, 5, 6));
+        let solution = maze.solve();
+        let dxf = S::to_dxf(&maze, 20, Some(&solution), true, 96.0);
+        (maze, dxf)
+    }
+
+    /// Group codes and their values
+    fn pairs(dxf: &str) -> Vec<(u16, &str)> {
+        let lines: Vec<&str> = dxf.lines().collect();
+        assert!(lines.len().is_multiple_of(2));
+        lines.chunks(2).map(|pair| (pair[0].trim().parse().unwrap(), pair[1])).collect()
+    }
+
+    fn test_dxf_is_plain_r12<S: Shape>() {
+        let (_, dxf) = dxf::<S>();
+        let pairs = pairs(&dxf);
+        assert_eq!(pairs.last(), Some(&(0, "EOF")));
+        let version = pairs.iter().position(|&pair| pair == (9, "$ACADVER")).unwrap();
+        assert_eq!(pairs[version + 1], (1, "AC1009"));
+
+        // No handles or subclass markers, and polylines are VERTEX runs ended by SEQEND
+        assert!(pairs.iter().all(|&(c, _)| c != 5 && c != 100));
+        let count = |entity: &str| pairs.iter().filter(|&&pair| pair == (0, entity)).count();
+        assert_eq!(count("POLYLINE"), count("SEQEND"));
+        assert_eq!(count("LWPOLYLINE"), 0);
+    }
+
+    fn test_dxf_layers<S: Shape>() {
+        // Every entity is on a declared layer, and each layer is used
+        let (maze, dxf) = dxf::<S>();
+        let entity_layers: Vec<&str> = pairs(&dxf)
+            .windows(2)
+            .filter(|pair| pair[0].0 == 0 && !["VERTEX", "SEQEND"].contains(&pair[0].1) && pair[1].0 == 8)
+            .map(|pair| pair[1].1)
+            .collect();
+        for layer in ["walls", "border", "solution", "labels"] {
+            assert!(dxf.contains(&format!("  2\n{}\n", layer)));
+            assert!(entity_layers.contains(&layer), "nothing on {}", layer);
+        }
+        assert_eq!(entity_layers.iter().filter(|&&l| l == "labels").count(), maze.cells.len());
+        assert_eq!(entity_layers.iter().filter(|&&l| l == "solution").count(), 1);
+    }
+
+    fn test_dxf_is_in_millimetres<S: Shape>() {
+        // Walls come out in millimetres, within the drawing's extent
+        let (maze, dxf) = dxf::<S>();
+        let pairs = pairs(&dxf);
+        let plot = S::to_plot(&maze, 20, None);
+        let (width, height) = (plot.width * 25.4 / 96.0, plot.height * 25.4 / 96.0);
+        let coordinates = |code: u16| pairs.iter().filter(move |&&(c, _)| c == code).map(|&(_, v)| v.parse::<f64>().unwrap());
+        assert!(coordinates(10).all(|x| (0.0..=width + 0.001).contains(&x)));
+        assert!(coordinates(20).all(|y| (0.0..=height + 0.001).contains(&y)));
+    }
+
+    shape_tests!(test_dxf_is_plain_r12, test_dxf_layers, test_dxf_is_in_millimetres);
+}
@@ -0,0 +1,253 @@
+// G-code output for pen plotters and CNC routers, driven by the merged and
+// ordered strokes of a `Plot`. Drawing units are converted to machine units
+// at a given number of drawing units per inch, and y is flipped so the maze
+// appears the right way up on a machine whose y axis points away from the
+// operator.
+use std::str::FromStr;
+
+use super::{decimal, Plot, Point};
+
+/// How the tool is raised or lowered
+#[derive(Debug, Clone, PartialEq)]
+pub enum PenCommand {
+    /// Move the Z axis to this height, in machine units
+    Z(f64),
+    /// Send this line as is, e.g. a servo command such as "M3 S90"
+    Command(String),
+}
+
+impl FromStr for PenCommand {
+    type Err = String;
+
+    /// A number is a Z height; anything else is a literal G-code line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("pen command must not be empty".to_string());
+        }
+        match s.parse::<f64>() {
+            Ok(z) if z.is_finite() => Ok(PenCommand::Z(z)),
+            Ok(_) => Err(format!("{} is not a valid Z height", s)),
+            Err(_) => Ok(PenCommand::Command(s.to_string())),
+        }
+    }
+}
+
+/// Where the machine origin sits on the maze
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Origin {
+    BottomLeft,
+    TopLeft,
+    Center,
+}
+
+impl FromStr for Origin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bottom-left" => Ok(Origin::BottomLeft),
+            "top-left" => Ok(Origin::TopLeft),
+            "center" => Ok(Origin::Center),
+            _ => Err(format!("unknown origin '{}', expected bottom-left, top-left or center", s)),
+        }
+    }
+}
+
+/// Machine units, selected with G21 or G20
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Units {
+    Millimetres,
+    Inches,
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mm" => Ok(Units::Millimetres),
+            "in" | "inch" => Ok(Units::Inches),
+            _ => Err(format!("unknown units '{}', expected mm or in", s)),
+        }
+    }
+}
+
+/// Machine settings for G-code output
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeOptions {
+    /// Drawing units per inch
+    pub dpi: f64,
+    pub units: Units,
+    pub origin: Origin,
+    /// Cutting or drawing speed, in machine units per minute
+    pub feed_rate: f64,
+    pub pen_up: PenCommand,
+    pub pen_down: PenCommand,
+}
+
+impl GcodeOptions {
+    /// Machine units per drawing unit
+    fn scale(&self) -> f64 {
+        match self.units {
+            Units::Millimetres => 25.4 / self.dpi,
+            Units::Inches => 1.0 / self.dpi,
+        }
+    }
+
+    /// Convert a drawing point to machine coordinates
+    fn machine(&self, plot: &Plot, (x, y): Point) -> Point {
+        let (x, y) = match self.origin {
+            Origin::BottomLeft => (x, plot.height - y),
+            Origin::TopLeft => (x, -y),
+            Origin::Center => (x - plot.width / 2.0, plot.height / 2.0 - y),
+        };
+        (x * self.scale(), y * self.scale())
+    }
+
+    fn pen(&self, command: &PenCommand, rapid: bool) -> String {
+        match command {
+            PenCommand::Z(z) if rapid => format!("G0 Z{}", coordinate(*z)),
+            PenCommand::Z(z) => format!("G1 Z{} F{}", coordinate(*z), coordinate(self.feed_rate)),
+            PenCommand::Command(line) => line.clone(),
+        }
+    }
+}
+
+impl Plot {
+    /// G-code drawing the walls
+    pub fn to_gcode(&self, options: &GcodeOptions, seed: Option<u64>) -> String {
+        let mut gcode = preamble(self, options, seed);
+        toolpath(&mut gcode, self, options, "walls", &self.paths);
+        finish(&mut gcode);
+        gcode
+    }
+
+    /// G-code drawing the walls, then pausing (M0) for a change of pen or
+    /// tool before the solution. With `separate`, only the solution is drawn,
+    /// as a job of its own.
+    pub fn solution_gcode(&self, options: &GcodeOptions, seed: Option<u64>, separate: bool) -> String {
+        let mut gcode = preamble(self, options, seed);
+        if !separate {
+            toolpath(&mut gcode, self, options, "walls", &self.paths);
+            gcode.push_str("M0 (change pen or tool for the solution)\n");
+        }
+        if self.solution.len() > 1 {
+            toolpath(&mut gcode, self, options, "solution", std::slice::from_ref(&self.solution));
+        }
+        finish(&mut gcode);
+        gcode
+    }
+}
+
+/// Format a machine coordinate with at most three decimals
+fn coordinate(value: f64) -> String {
+    decimal(value, 3)
+}
+
+fn preamble(plot: &Plot, options: &GcodeOptions, seed: Option<u64>) -> String {
+    let (units, name) = match options.units {
+        Units::Millimetres => ("G21", "mm"),
+        Units::Inches => ("G20", "in"),
+    };
+    let mut gcode = String::from("(maze");
+    if let Some(seed) = seed {
+        gcode.push_str(&format!(" seed={}", seed));
+    }
+    gcode.push_str(&format!(
+        ")\n({} x {} {})\n{} G90\n{}\n",
+        coordinate(plot.width * options.scale()),
+        coordinate(plot.height * options.scale()),
+        name,
+        units,
+        options.pen(&options.pen_up, true)
+    ));
+    gcode
+}
+
+/// Draw each path with the pen down, travelling between them with it up
+fn toolpath(gcode: &mut String, plot: &Plot, options: &GcodeOptions, layer: &str, paths: &[Vec<Point>]) {
+    gcode.push_str(&format!("({})\n", layer));
+    for path in paths {
+        let start = options.machine(plot, path[0]);
+        gcode.push_str(&format!("G0 X{} Y{}\n", coordinate(start.0), coordinate(start.1)));
+        gcode.push_str(&options.pen(&options.pen_down, false));
+        gcode.push('\n');
+        for (i, &point) in path[1..].iter().enumerate() {
+            let (x, y) = options.machine(plot, point);
+            gcode.push_str(&format!("G1 X{} Y{}", coordinate(x), coordinate(y)));
+            // Feed rate is modal; set it on the first cutting move of each path
+            if i == 0 {
+                gcode.push_str(&format!(" F{}", coordinate(options.feed_rate)));
+            }
+            gcode.push('\n');
+        }
+        gcode.push_str(&options.pen(&options.pen_up, true));
+        gcode.push('\n');
+    }
+}
+
+/// Return to the origin with the pen up and end the program
+fn finish(gcode: &mut String) {
+    gcode.push_str("G0 X0 Y0\nM2\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::Shape;
+    use crate::shapes::HexShape;
+    use crate::testutil::{self, build};
+
+    #[test]
+    fn test_gcode_output() {
+        assert_eq!("M3 S90".parse(), Ok(PenCommand::Command("M3 S90".to_string())));
+        assert_eq!("-1.5".parse(), Ok(PenCommand::Z(-1.5)));
+
+        let mut options = GcodeOptions {
+            dpi: 96.0,
+            units: Units::Millimetres,
+            origin: Origin::BottomLeft,
+            feed_rate: 1000.0,
+            pen_up: PenCommand::Z(5.0),
+            pen_down: PenCommand::Z(-1.5),
+        };
+        let maze = build::<HexShape>(&testutil::options::<HexShape>(8, 6, 2));
+        let plot = HexShape::to_plot(&maze, 20, Some(&maze.solve()));
+        let moves = |gcode: &str, prefix: &str| -> Vec<(f64, f64)> {
+            gcode
+                .lines()
+                .filter(|line| line.starts_with(prefix) && line.contains(" X"))
+                .map(|line| {
+                    let value = |axis: char| {
+                        let word = line.split(' ').find(|word| word.starts_with(axis)).unwrap();
+                        word[1..].parse::<f64>().unwrap()
+                    };
+                    (value('X'), value('Y'))
+                })
+                .collect()
+        };
+
+        // Bottom-left origin in mm: everything lands inside the maze's physical size
+        let gcode = plot.to_gcode(&options, maze.seed);
+        assert!(gcode.starts_with("(maze seed=2)\n") && gcode.contains("G21 G90\n") && gcode.ends_with("M2\n"));
+        assert_eq!(gcode.matches("G1 Z-1.5").count(), plot.paths.len());
+        let (width, height) = (plot.width * 25.4 / 96.0, plot.height * 25.4 / 96.0);
+        let all: Vec<(f64, f64)> = [moves(&gcode, "G0"), moves(&gcode, "G1")].concat();
+        assert!(all.iter().all(|&(x, y)| (0.0..=width + 0.001).contains(&x) && (0.0..=height + 0.001).contains(&y)));
+
+        // Centered, in inches: the same moves, shifted and scaled
+        options.origin = Origin::Center;
+        options.units = Units::Inches;
+        let centered = moves(&plot.to_gcode(&options, None), "G1");
+        let expected = moves(&gcode, "G1").into_iter().map(|(x, y)| ((x - width / 2.0) / 25.4, (y - height / 2.0) / 25.4));
+        assert!(centered.iter().zip(expected).all(|(a, b)| (a.0 - b.0).abs() < 0.001 && (a.1 - b.1).abs() < 0.001));
+
+        // The solution follows the walls after a pause, or stands alone
+        let together = plot.solution_gcode(&options, None, false);
+        assert!(together.find("(walls)") < together.find("M0") && together.find("M0") < together.find("(solution)"));
+        let separate = plot.solution_gcode(&options, None, true);
+        assert!(!separate.contains("(walls)") && !separate.contains("M0") && separate.contains("(solution)"));
+        assert_eq!(moves(&separate, "G1").len(), plot.solution.len() - 1);
+    }
+}
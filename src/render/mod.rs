@@ -0,0 +1,136 @@
+// Output backends. Shapes describe a maze as a handful of drawing calls on a
+// `Painter`; each backend turns those calls into a file format. Text output
+// traces the same drawing onto a character grid, unless a shape lays out its
+// own text.
+mod dxf;
+mod gcode;
+mod pdf;
+mod plot;
+mod png;
+mod raster;
+mod stl;
+mod svg;
+mod text;
+mod theme;
+mod underlay;
+
+pub use gcode::{GcodeOptions, Origin, PenCommand, Units};
+pub use pdf::{mm_to_points, write_pdf, PageSize, PdfPage};
+pub use plot::Plot;
+pub use stl::StlOptions;
+pub use text::TextStyle;
+pub use theme::{CellFill, Color, Theme};
+
+pub(crate) use dxf::DxfPainter;
+pub(crate) use pdf::PdfPainter;
+pub(crate) use plot::PlotPainter;
+pub(crate) use raster::RasterPainter;
+pub(crate) use stl::StlPainter;
+pub(crate) use svg::SvgPainter;
+pub(crate) use text::{box_junction, junction_arms, CharGrid, TextPainter};
+pub(crate) use theme::heat_color;
+pub(crate) use underlay::Underlay;
+
+/// A point in drawing units (SVG pixels), measured from the top-left corner
+pub type Point = (f64, f64);
+
+/// A colour as red, green and blue channels
+pub type Rgb = [u8; 3];
+
+/// Resolution that one drawing unit corresponds to, as in CSS
+pub(crate) const BASE_DPI: f64 = 96.0;
+
+/// A circular arc, running from angle `start` to `end` (radians from the
+/// positive x axis). Since y points down, growing angles turn clockwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arc {
+    pub center: Point,
+    pub radius: f64,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Arc {
+    /// Furthest the chords from `points` stray from the true curve, in drawing units
+    const TOLERANCE: f64 = 0.05;
+
+    pub fn point(&self, angle: f64) -> Point {
+        (self.center.0 + self.radius * angle.cos(), self.center.1 + self.radius * angle.sin())
+    }
+
+    /// Points along the arc from start to end, close enough together that
+    /// straight lines between them follow the curve
+    pub fn points(&self) -> Vec<Point> {
+        // A chord spanning angle a strays radius * (1 - cos(a / 2)) from the arc
+        let step = 2.0 * (1.0 - Self::TOLERANCE / self.radius).clamp(-1.0, 1.0).acos();
+        let chords = ((self.end - self.start) / step).ceil().max(1.0) as usize;
+        (0..=chords)
+            .map(|i| self.point(self.start + (self.end - self.start) * i as f64 / chords as f64))
+            .collect()
+    }
+}
+
+/// Drawing operations used by the shape renderers
+pub trait Painter {
+    /// Start a drawing of the given size. Called once, before anything else.
+    fn begin(&mut self, width: f64, height: f64);
+
+    /// Draw one wall segment
+    fn wall(&mut self, from: Point, to: Point);
+
+    /// Draw one segment of the outer wall; drawn like any other wall unless
+    /// the backend keeps it apart
+    fn border(&mut self, from: Point, to: Point) {
+        self.wall(from, to);
+    }
+
+    /// Draw a wall along a circular arc; backends without curves draw it as
+    /// the straight lines between `Arc::points`
+    fn arc_wall(&mut self, arc: &Arc) {
+        for pair in arc.points().windows(2) {
+            self.wall(pair[0], pair[1]);
+        }
+    }
+
+    /// Draw an arc of the outer wall, as `border` is to `wall`
+    fn arc_border(&mut self, arc: &Arc) {
+        for pair in arc.points().windows(2) {
+            self.border(pair[0], pair[1]);
+        }
+    }
+
+    /// Fill a polygon with a solid colour, underneath the walls; ignored by
+    /// backends that only draw lines
+    fn fill(&mut self, _polygon: &[Point], _color: Rgb) {}
+
+    /// Draw a debug label centered on a point
+    fn label(&mut self, at: Point, font_size: f64, text: &str);
+
+    /// Draw the solution path through the given cell centers
+    fn solution(&mut self, points: &[Point]);
+}
+
+/// Format a coordinate with at most two decimals and no trailing zeros
+fn num(value: f64) -> String {
+    decimal(value, 2)
+}
+
+/// Format a value with at most `places` decimals and no trailing zeros
+fn decimal(value: f64, places: usize) -> String {
+    // Most coordinates are whole multiples of the last place; formatting a
+    // float to a precision is slow enough to dominate large drawings
+    let scale = 10u64.pow(places as u32);
+    let scaled = value * scale as f64;
+    if scaled == scaled.trunc() && scaled.abs() < 1e15 {
+        let sign = if scaled < 0.0 { "-" } else { "" };
+        let (whole, fraction) = ((scaled.abs() as u64) / scale, (scaled.abs() as u64) % scale);
+        if fraction == 0 {
+            return format!("{}{}", sign, whole);
+        }
+        let fraction = format!("{:0width$}", fraction, width = places);
+        return format!("{}{}.{}", sign, whole, fraction.trim_end_matches('0'));
+    }
+    let text = format!("{:.*}", places, value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
@@ -0,0 +1,313 @@
+// PDF output: each maze drawing becomes one page of vector content, scaled
+// to fit the printable area of the chosen paper size and centered on it.
+use std::str::FromStr;
+
+use super::{decimal, num, Painter, Point, Rgb, Theme};
+
+const POINTS_PER_INCH: f64 = 72.0;
+const POINTS_PER_MM: f64 = POINTS_PER_INCH / 25.4;
+
+/// Paper size in PDF points (1/72 inch)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PageSize {
+    pub const LETTER: PageSize = PageSize { width: 612.0, height: 792.0 };
+    pub const LEGAL: PageSize = PageSize { width: 612.0, height: 1008.0 };
+    pub const A4: PageSize = PageSize { width: 210.0 * POINTS_PER_MM, height: 297.0 * POINTS_PER_MM };
+    pub const A3: PageSize = PageSize { width: 297.0 * POINTS_PER_MM, height: 420.0 * POINTS_PER_MM };
+}
+
+impl FromStr for PageSize {
+    type Err = String;
+
+    /// Parse "letter", "legal", "a4", "a3", or a custom "WIDTHxHEIGHT" with
+    /// a unit suffix of mm (the default), in or pt, e.g. "8.5x11in"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "letter" => return Ok(PageSize::LETTER),
+            "legal" => return Ok(PageSize::LEGAL),
+            "a4" => return Ok(PageSize::A4),
+            "a3" => return Ok(PageSize::A3),
+            _ => {}
+        }
+
+        let (dimensions, scale) = if let Some(rest) = lower.strip_suffix("mm") {
+            (rest, POINTS_PER_MM)
+        } else if let Some(rest) = lower.strip_suffix("in") {
+            (rest, POINTS_PER_INCH)
+        } else if let Some(rest) = lower.strip_suffix("pt") {
+            (rest, 1.0)
+        } else {
+            (lower.as_str(), POINTS_PER_MM)
+        };
+        let invalid = || format!("invalid page size '{}', expected letter, legal, a4, a3 or e.g. 200x300mm", s);
+        let (width, height) = dimensions.split_once('x').ok_or_else(invalid)?;
+        let width: f64 = width.trim().parse().map_err(|_| invalid())?;
+        let height: f64 = height.trim().parse().map_err(|_| invalid())?;
+        if !(width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite()) {
+            return Err(invalid());
+        }
+        Ok(PageSize { width: width * scale, height: height * scale })
+    }
+}
+
+/// Convert a margin in millimetres to points
+pub fn mm_to_points(mm: f64) -> f64 {
+    mm * POINTS_PER_MM
+}
+
+/// One page of drawing commands, in drawing units before fitting to the paper
+pub struct PdfPage {
+    width: f64,
+    height: f64,
+    content: String,
+}
+
+/// Painter producing the content of one PDF page
+pub struct PdfPainter {
+    theme: Theme,
+    width: f64,
+    height: f64,
+    fills: String,
+    walls: String,
+    labels: String,
+    solution: String,
+}
+
+impl PdfPainter {
+    pub fn new(theme: &Theme) -> Self {
+        PdfPainter {
+            theme: theme.clone(),
+            width: 0.0,
+            height: 0.0,
+            fills: String::new(),
+            walls: String::new(),
+            labels: String::new(),
+            solution: String::new(),
+        }
+    }
+
+    pub fn finish(self) -> PdfPage {
+        let theme = &self.theme;
+        let mut content = String::new();
+        // A white background is the paper itself
+        if theme.background.0 != [255, 255, 255] {
+            content.push_str(&format!(
+                "{} rg 0 0 {} {} re f\n",
+                components(theme.background.0),
+                num(self.width),
+                num(self.height)
+            ));
+        }
+        if !self.fills.is_empty() {
+            // Each fill is also outlined in its own colour to close the gaps
+            // anti-aliasing leaves between neighbours
+            content.push_str(&format!("0.5 w 1 j\n{}", self.fills));
+        }
+        if !self.walls.is_empty() {
+            // Projecting square caps
+            content.push_str(&format!(
+                "{} RG {} w 2 J\n{}S\n",
+                components(theme.wall_color.0),
+                num(theme.wall_width),
+                self.walls
+            ));
+        }
+        if !self.labels.is_empty() {
+            content.push_str(&format!("{} rg\n{}", components(theme.label_color.0), self.labels));
+        }
+        if !self.solution.is_empty() {
+            // Round caps and joins
+            let dash = if theme.solution_dash.is_empty() {
+                String::new()
+            } else {
+                let lengths: Vec<String> = theme.solution_dash.iter().map(|&length| num(length)).collect();
+                format!(" [{}] 0 d", lengths.join(" "))
+            };
+            content.push_str(&format!(
+                "{} RG {} w 1 J 1 j{}\n{}S\n",
+                components(theme.solution_color.0),
+                num(theme.solution_width),
+                dash,
+                self.solution
+            ));
+        }
+        PdfPage { width: self.width, height: self.height, content }
+    }
+}
+
+impl Painter for PdfPainter {
+    fn begin(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.walls
+            .push_str(&format!("{} {} m {} {} l\n", num(from.0), num(from.1), num(to.0), num(to.1)));
+    }
+
+    fn fill(&mut self, polygon: &[Point], color: Rgb) {
+        let color = components(color);
+        self.fills.push_str(&format!("{} rg {} RG\n", color, color));
+        for (i, &(x, y)) in polygon.iter().enumerate() {
+            let operator = if i == 0 { "m" } else { "l" };
+            self.fills.push_str(&format!("{} {} {}\n", num(x), num(y), operator));
+        }
+        self.fills.push_str("h B\n");
+    }
+
+    fn label(&mut self, at: Point, font_size: f64, text: &str) {
+        // Courier glyphs are 0.6 em wide; the text matrix flips y back upright
+        let x = at.0 - text.chars().count() as f64 * 0.6 * font_size / 2.0;
+        let y = at.1 + font_size / 3.0;
+        self.labels.push_str(&format!(
+            "BT /F1 {} Tf 1 0 0 -1 {} {} Tm ({}) Tj ET\n",
+            num(font_size),
+            num(x),
+            num(y),
+            escape(text)
+        ));
+    }
+
+    fn solution(&mut self, points: &[Point]) {
+        for (i, &(x, y)) in points.iter().enumerate() {
+            let operator = if i == 0 { "m" } else { "l" };
+            self.solution.push_str(&format!("{} {} {}\n", num(x), num(y), operator));
+        }
+    }
+}
+
+/// A colour as PDF's three 0-1 operands
+fn components(color: Rgb) -> String {
+    let [r, g, b] = color.map(|channel| decimal(channel as f64 / 255.0, 3));
+    format!("{} {} {}", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)")
+}
+
+/// Assemble pages into a PDF document. Each page is scaled (up or down) to
+/// the largest size that fits inside the margins and centered.
+pub fn write_pdf(pages: &[PdfPage], size: PageSize, margin: f64, seed: Option<u64>) -> Vec<u8> {
+    // Objects: 1 catalog, 2 page tree, 3 font, 4 info, then a page and its
+    // content stream for each page
+    let page_id = |i: usize| 5 + 2 * i;
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", page_id(i))).collect();
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_vec());
+    let subject = seed.map(|seed| format!(" /Subject (seed={})", seed)).unwrap_or_default();
+    objects.push(format!("<< /Producer (maze){} >>", subject).into_bytes());
+
+    let available = ((size.width - 2.0 * margin).max(1.0), (size.height - 2.0 * margin).max(1.0));
+    for (i, page) in pages.iter().enumerate() {
+        let scale = (available.0 / page.width.max(1.0)).min(available.1 / page.height.max(1.0));
+        let left = (size.width - page.width * scale) / 2.0;
+        let top = (size.height + page.height * scale) / 2.0;
+
+        // Flip y so drawing units (origin top-left) map onto the page
+        let content = format!(
+            "q {:.6} 0 0 {:.6} {} {} cm\n{}Q\n",
+            scale,
+            -scale,
+            num(left),
+            num(top),
+            page.content
+        );
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                num(size.width),
+                num(size.height),
+                page_id(i) + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content.as_bytes());
+        stream.extend_from_slice(b"endstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref = out.len();
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::{GenericMaze, Shape};
+    use crate::testutil::{build, options, shape_tests};
+
+    #[test]
+    fn test_page_sizes() {
+        assert_eq!("A4".parse::<PageSize>().unwrap(), PageSize::A4);
+        assert_eq!("8.5x11in".parse::<PageSize>().unwrap(), PageSize::LETTER);
+        assert_eq!("100x50".parse::<PageSize>().unwrap(), "100x50mm".parse::<PageSize>().unwrap());
+        assert!("0x50mm".parse::<PageSize>().is_err());
+        assert!("huge".parse::<PageSize>().is_err());
+    }
+
+    /// A two-page PDF of a maze and its solution, with 10mm margins on A4
+    fn pdf<S: Shape>() -> (GenericMaze<S>, String) {
+        let maze = build::<S>(&options::<S>(30, 20, 4));
+        let solution = maze.solve();
+        let pages = [S::pdf_page(&maze, 20, None, true, &Theme::default()), S::pdf_page(&maze, 20, Some(&solution), true, &Theme::default())];
+        let pdf = write_pdf(&pages, PageSize::A4, mm_to_points(10.0), maze.seed);
+        (maze, String::from_utf8_lossy(&pdf).to_string())
+    }
+
+    fn test_pdf_structure<S: Shape>() {
+        let (_, text) = pdf::<S>();
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/Subject (seed=4)"));
+
+        // Every cross-reference entry points at its object
+        let xref: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        for (i, entry) in text[xref..].lines().skip(3).take_while(|line| line.ends_with(" n ")).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    fn test_pdf_fills_width_between_margins<S: Shape>() {
+        let (maze, text) = pdf::<S>();
+        let scale: f64 = text.split("q ").nth(1).unwrap().split(' ').next().unwrap().parse().unwrap();
+        let svg = S::to_svg(&maze, 20, None, false, &Theme::default());
+        let width: f64 = svg.split("width=\"").nth(1).unwrap().split('"').next().unwrap().parse().unwrap();
+        assert!((width * scale - (PageSize::A4.width - 2.0 * mm_to_points(10.0))).abs() < 0.01);
+    }
+
+    shape_tests!(test_pdf_structure, test_pdf_fills_width_between_margins);
+}
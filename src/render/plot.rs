@@ -0,0 +1,400 @@
+// Output for pen plotters and laser cutters. Shapes draw every wall of every
+// cell, so walls shared by two cells arrive twice and long straight walls
+// arrive as one segment per cell. Here the segments are deduplicated, chained
+// into polylines the pen can follow without lifting, and ordered so that the
+// pen-up travel between polylines stays short.
+use std::collections::{HashMap, HashSet};
+
+use super::{num, Color, Painter, Point, Theme};
+
+/// Endpoints closer than this (in drawing units) are treated as the same point
+const SNAP: f64 = 0.01;
+
+/// Painter collecting wall segments for plotting. Debug labels are dropped,
+/// since text can't be cut or plotted as strokes.
+#[derive(Default)]
+pub struct PlotPainter {
+    width: f64,
+    height: f64,
+    segments: Vec<(Point, Point)>,
+    solution: Vec<Point>,
+}
+
+/// A drawing as continuous pen strokes, in plotting order
+pub struct Plot {
+    pub width: f64,
+    pub height: f64,
+    /// Wall polylines, each drawn without lifting the pen
+    pub paths: Vec<Vec<Point>>,
+    /// Solution path through the cell centers, empty if there is none
+    pub solution: Vec<Point>,
+}
+
+impl PlotPainter {
+    pub fn new() -> Self {
+        PlotPainter::default()
+    }
+
+    /// Merge the collected walls into ordered polylines
+    pub fn finish(self) -> Plot {
+        Plot {
+            width: self.width,
+            height: self.height,
+            paths: strokes(&self.segments),
+            solution: self.solution,
+        }
+    }
+}
+
+impl Painter for PlotPainter {
+    fn begin(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.segments.push((from, to));
+    }
+
+    fn label(&mut self, _at: Point, _font_size: f64, _text: &str) {}
+
+    fn solution(&mut self, points: &[Point]) {
+        self.solution = points.to_vec();
+    }
+}
+
+impl Plot {
+    /// SVG with one `<polyline>` per stroke and no background, so cutters
+    /// see only the walls. The solution, if any, is a separate group. Only the
+    /// theme's wall and solution colours and widths apply: cutters take
+    /// strokes as they are, without fills or dashes.
+    pub fn to_svg(&self, seed: Option<u64>, theme: &Theme) -> String {
+        let (width, height) = (num(self.width), num(self.height));
+        let mut svg = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
+"#,
+            width, height, width, height
+        );
+        if let Some(seed) = seed {
+            svg.push_str(&format!("  <metadata>seed={}</metadata>\n", seed));
+        }
+
+        let group = |svg: &mut String, id: &str, color: Color, stroke_width: f64, paths: &[&[Point]]| {
+            svg.push_str(&format!(
+                "  <g id=\"{}\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\" fill=\"none\">\n",
+                id,
+                color,
+                num(stroke_width)
+            ));
+            for path in paths {
+                let points: Vec<String> = path.iter().map(|&(x, y)| format!("{},{}", num(x), num(y))).collect();
+                svg.push_str(&format!("    <polyline points=\"{}\"/>\n", points.join(" ")));
+            }
+            svg.push_str("  </g>\n");
+        };
+        let walls: Vec<&[Point]> = self.paths.iter().map(Vec::as_slice).collect();
+        group(&mut svg, "walls", theme.wall_color, theme.wall_width, &walls);
+        if self.solution.len() > 1 {
+            group(&mut svg, "solution", theme.solution_color, theme.solution_width, &[&self.solution]);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Merge segments into polylines in plotting order
+pub(super) fn strokes(segments: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    order_paths(merge_segments(segments))
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Segments joined at shared endpoints, with duplicates removed
+pub(super) struct SegmentGraph {
+    /// Distinct endpoints
+    pub points: Vec<Point>,
+    /// Segments as pairs of point indices
+    pub edges: Vec<(usize, usize)>,
+    /// Indices of the edges meeting at each point
+    pub adjacent: Vec<Vec<usize>>,
+}
+
+impl SegmentGraph {
+    pub fn new(segments: &[(Point, Point)]) -> Self {
+        let mut ids: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut points: Vec<Point> = Vec::new();
+        let mut vertex = |p: Point| {
+            let key = ((p.0 / SNAP).round() as i64, (p.1 / SNAP).round() as i64);
+            *ids.entry(key).or_insert_with(|| {
+                points.push(p);
+                points.len() - 1
+            })
+        };
+
+        let mut seen = HashSet::new();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for &(from, to) in segments {
+            let (a, b) = (vertex(from), vertex(to));
+            if a != b && seen.insert((a.min(b), a.max(b))) {
+                edges.push((a, b));
+            }
+        }
+
+        let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+        for (edge, &(a, b)) in edges.iter().enumerate() {
+            adjacent[a].push(edge);
+            adjacent[b].push(edge);
+        }
+        SegmentGraph { points, edges, adjacent }
+    }
+
+    /// The point at the other end of an edge
+    pub fn other_end(&self, edge: usize, point: usize) -> usize {
+        let (a, b) = self.edges[edge];
+        if a == point { b } else { a }
+    }
+}
+
+/// Chain segments into polylines. Duplicate segments are dropped, and at each
+/// junction the walk carries straight on where it can, so runs of collinear
+/// walls become single straight strokes.
+fn merge_segments(segments: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let SegmentGraph { points, edges, adjacent } = SegmentGraph::new(segments);
+    let mut used = vec![false; edges.len()];
+    let mut unused_degree: Vec<usize> = adjacent.iter().map(Vec::len).collect();
+    let mut paths = Vec::new();
+
+    // Start from odd-degree vertices first: every polyline must end at one,
+    // so this leaves only closed loops for the second pass
+    let odd = (0..points.len()).filter(|&v| adjacent[v].len() % 2 == 1);
+    let starts: Vec<usize> = odd.chain(0..points.len()).collect();
+    for start in starts {
+        while unused_degree[start] > 0 {
+            let mut path = vec![start];
+            let mut current = start;
+            let mut heading: Option<Point> = None;
+            loop {
+                // Prefer the unused edge closest to straight ahead
+                let next = adjacent[current]
+                    .iter()
+                    .copied()
+                    .filter(|&edge| !used[edge])
+                    .map(|edge| {
+                        let (a, b) = edges[edge];
+                        let other = if a == current { b } else { a };
+                        let length = distance(points[current], points[other]);
+                        let direction = (
+                            (points[other].0 - points[current].0) / length,
+                            (points[other].1 - points[current].1) / length,
+                        );
+                        let alignment = heading.map_or(0.0, |h| h.0 * direction.0 + h.1 * direction.1);
+                        (edge, other, direction, alignment)
+                    })
+                    .fold(None, |best: Option<(usize, usize, Point, f64)>, candidate| match best {
+                        Some(best) if best.3 >= candidate.3 => Some(best),
+                        _ => Some(candidate),
+                    });
+                let Some((edge, other, direction, _)) = next else { break };
+                used[edge] = true;
+                unused_degree[current] -= 1;
+                unused_degree[other] -= 1;
+                path.push(other);
+                current = other;
+                heading = Some(direction);
+            }
+            paths.push(path.into_iter().map(|v| points[v]).collect());
+        }
+    }
+    paths
+}
+
+/// Drop points in the middle of straight runs
+fn simplify(path: Vec<Point>) -> Vec<Point> {
+    let mut simplified: Vec<Point> = Vec::with_capacity(path.len());
+    for point in path {
+        if let [.., before, middle] = simplified[..] {
+            let cross = (middle.0 - before.0) * (point.1 - middle.1) - (middle.1 - before.1) * (point.0 - middle.0);
+            let dot = (middle.0 - before.0) * (point.0 - middle.0) + (middle.1 - before.1) * (point.1 - middle.1);
+            if cross.abs() < SNAP * SNAP && dot > 0.0 {
+                simplified.pop();
+            }
+        }
+        simplified.push(point);
+    }
+    simplified
+}
+
+/// Order polylines greedily, always moving to the nearest unplotted end
+/// (reversing the polyline if needed), starting from the origin. A polyline
+/// starting where the previous one ended is joined onto it. Ends are bucketed
+/// on a grid so each step only searches the buckets nearby.
+fn order_paths(mut paths: Vec<Vec<Point>>) -> Vec<Vec<Point>> {
+    // End 2 * i is the first point of path i, end 2 * i + 1 its last
+    let ends: Vec<Point> = paths.iter().flat_map(|path| [path[0], path[path.len() - 1]]).collect();
+    let (max_x, max_y) = ends.iter().fold((0.0f64, 0.0f64), |(x, y), p| (x.max(p.0), y.max(p.1)));
+    let bucket_size = (max_x.max(max_y) / (paths.len().max(1) as f64).sqrt()).max(1.0);
+    let columns = (max_x / bucket_size) as usize + 1;
+    let rows = (max_y / bucket_size) as usize + 1;
+    let bucket_of = |p: Point| {
+        let column = ((p.0 / bucket_size).max(0.0) as usize).min(columns - 1);
+        let row = ((p.1 / bucket_size).max(0.0) as usize).min(rows - 1);
+        (column, row)
+    };
+
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); columns * rows];
+    for (end, &point) in ends.iter().enumerate() {
+        let (column, row) = bucket_of(point);
+        buckets[row * columns + column].push(end);
+    }
+
+    let mut ordered: Vec<Vec<Point>> = Vec::with_capacity(paths.len());
+    let mut position: Point = (0.0, 0.0);
+    for _ in 0..paths.len() {
+        let (cx, cy) = bucket_of(position);
+        let (cx, cy) = (cx as i64, cy as i64);
+        let last_ring = cx.max(columns as i64 - 1 - cx).max(cy).max(rows as i64 - 1 - cy);
+        let mut best: Option<(usize, f64)> = None;
+        for ring in 0..=last_ring {
+            // Every end in this ring or beyond is at least this far away
+            if let Some((_, best_distance)) = best
+                && best_distance <= (ring - 1) as f64 * bucket_size
+            {
+                break;
+            }
+            // Walk the square of buckets at this distance, clipped to the grid
+            let perimeter = (cx - ring..=cx + ring)
+                .flat_map(|x| [(x, cy - ring), (x, cy + ring)])
+                .chain((cy - ring + 1..cy + ring).flat_map(|y| [(cx - ring, y), (cx + ring, y)]));
+            for (x, y) in perimeter {
+                if x < 0 || y < 0 || x >= columns as i64 || y >= rows as i64 {
+                    continue;
+                }
+                for &end in &buckets[y as usize * columns + x as usize] {
+                    let d = distance(position, ends[end]);
+                    if best.is_none_or(|(_, best_distance)| d < best_distance) {
+                        best = Some((end, d));
+                    }
+                }
+            }
+        }
+
+        let Some((end, travel)) = best else { break };
+        let index = end / 2;
+        for end in [index * 2, index * 2 + 1] {
+            let (column, row) = bucket_of(ends[end]);
+            buckets[row * columns + column].retain(|&e| e != end);
+        }
+        let mut path = std::mem::take(&mut paths[index]);
+        if end % 2 == 1 {
+            path.reverse();
+        }
+        position = path[path.len() - 1];
+        match ordered.last_mut() {
+            Some(last) if travel < SNAP => last.extend_from_slice(&path[1..]),
+            _ => ordered.push(path),
+        }
+    }
+    ordered.into_iter().map(simplify).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::{GenericMaze, Shape};
+    use crate::testutil::{build, options, shape_tests};
+
+    // Records walls exactly as the shapes draw them
+    #[derive(Default)]
+    struct Walls(Vec<(Point, Point)>);
+
+    impl Painter for Walls {
+        fn begin(&mut self, _width: f64, _height: f64) {}
+        fn wall(&mut self, from: Point, to: Point) {
+            self.0.push((from, to));
+        }
+        fn label(&mut self, _at: Point, _font_size: f64, _text: &str) {}
+        fn solution(&mut self, _points: &[Point]) {}
+    }
+
+    fn length((a, b): (Point, Point)) -> f64 {
+        (a.0 - b.0).hypot(a.1 - b.1)
+    }
+
+    fn plot_maze<S: Shape>() -> GenericMaze<S> {
+        build::<S>(&options::<S>(12, 9, 21))
+    }
+
+    fn test_plot_strokes_each_wall_once<S: Shape>() {
+        let maze = plot_maze::<S>();
+        let mut walls = Walls::default();
+        S::draw(&maze, 20, None, false, &mut walls);
+        let plot = S::to_plot(&maze, 20, None);
+
+        // Same lines as the shape drew, each stroked once
+        let key = |(a, b): (Point, Point)| {
+            let round = |p: Point| ((p.0 * 100.0).round() as i64, (p.1 * 100.0).round() as i64);
+            (round(a).min(round(b)), round(a).max(round(b)))
+        };
+        let unique: HashSet<_> = walls.0.iter().map(|&wall| key(wall)).collect();
+        let drawn: f64 = unique.iter().map(|&(a, b)| length(((a.0 as f64, a.1 as f64), (b.0 as f64, b.1 as f64))) / 100.0).sum();
+        let strokes: Vec<(Point, Point)> = plot.paths.iter().flat_map(|path| path.windows(2).map(|pair| (pair[0], pair[1]))).collect();
+        let plotted: f64 = strokes.iter().map(|&stroke| length(stroke)).sum();
+        assert!((drawn - plotted).abs() < 0.1, "drew {} but plotted {}", drawn, plotted);
+        let on_wall = |p: Point| walls.0.iter().any(|&(a, b)| (length((a, p)) + length((p, b)) - length((a, b))).abs() < 1e-6);
+        assert!(strokes.iter().all(|&(a, b)| on_wall(((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0))));
+
+        // Walls are merged into far fewer strokes
+        assert!(plot.paths.len() * 3 < unique.len(), "{} strokes", plot.paths.len());
+    }
+
+    fn travel(paths: &[Vec<Point>]) -> f64 {
+        let mut position = (0.0, 0.0);
+        let mut total = 0.0;
+        for path in paths {
+            total += length((position, path[0]));
+            position = path[path.len() - 1];
+        }
+        total
+    }
+
+    fn test_plot_ordering_reduces_travel<S: Shape>() {
+        // Nearest-end ordering moves the pen less than plotting in merge order would
+        let mut walls = Walls::default();
+        S::draw(&plot_maze::<S>(), 20, None, false, &mut walls);
+        let merged = merge_segments(&walls.0);
+        let ordered = order_paths(merged.clone());
+        assert!(travel(&ordered) < travel(&merged), "{} vs {}", travel(&ordered), travel(&merged));
+    }
+
+    #[test]
+    fn test_order_paths_searches_past_the_nearest_ring() {
+        // Nine paths within 30 units give 10-unit buckets. After the first path the
+        // pen is at (9.9, 5), near the right edge of its bucket: the end in the next
+        // ring is 12 away, the one two rings out only 10.3, so the search may stop
+        // only once a ring's lower bound (ring - 1) * 10 reaches the best distance
+        let mut paths = vec![
+            vec![(0.0, 0.0), (9.9, 5.0)],
+            vec![(9.9, 17.0), (9.9, 30.0)],
+            vec![(20.2, 5.0), (30.0, 5.0)],
+        ];
+        paths.extend((24..30).map(|x| vec![(x as f64, 29.0), (x as f64, 30.0)]));
+        let ordered = order_paths(paths);
+        assert_eq!(ordered[0], vec![(0.0, 0.0), (9.9, 5.0)]);
+        assert_eq!(ordered[1], vec![(20.2, 5.0), (30.0, 5.0)]);
+    }
+
+    fn test_plot_svg<S: Shape>() {
+        let maze = plot_maze::<S>();
+        let plot = S::to_plot(&maze, 20, None);
+        let solution = maze.solve();
+        let svg = S::to_plot(&maze, 20, Some(&solution)).to_svg(maze.seed, &Theme::default());
+        assert_eq!(svg.matches("<polyline").count(), plot.paths.len() + 1);
+        assert!(svg.contains("id=\"solution\"") && !svg.contains("<rect") && !svg.contains("<line"));
+    }
+
+    shape_tests!(test_plot_strokes_each_wall_once, test_plot_ordering_reduces_travel, test_plot_svg);
+}
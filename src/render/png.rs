@@ -0,0 +1,458 @@
+// Minimal PNG encoder: 8-bit RGB, one IDAT chunk compressed with a single
+// fixed-Huffman deflate block. Maze images are mostly long runs of identical
+// pixels and rows, which plain LZ77 with per-row filtering handles well.
+use super::raster::Image;
+use crate::packed::crc32;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Encode an image as PNG, recording its resolution and the maze seed
+pub fn encode(image: &Image, dpi: f64, seed: Option<u64>) -> Vec<u8> {
+    let mut out = SIGNATURE.to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // Bit depth 8, colour type 2 (RGB), default compression, filtering and no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header);
+
+    let pixels_per_metre = (dpi / 0.0254).round() as u32;
+    let mut physical = Vec::with_capacity(9);
+    physical.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    physical.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    physical.push(1); // unit: metre
+    write_chunk(&mut out, b"pHYs", &physical);
+
+    if let Some(seed) = seed {
+        write_chunk(&mut out, b"tEXt", format!("Comment\0seed={}", seed).as_bytes());
+    }
+
+    write_chunk(&mut out, b"IDAT", &zlib(&filter_rows(image)));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let checksum = crc32(&out[start..]);
+    out.extend_from_slice(&checksum.to_be_bytes());
+}
+
+/// Prefix each row with a filter type, choosing per row between None, Sub
+/// and Up by the smallest sum of absolute residuals
+fn filter_rows(image: &Image) -> Vec<u8> {
+    let stride = image.width * 3;
+    let mut out = Vec::with_capacity((stride + 1) * image.height);
+    let zero_row = vec![0u8; stride];
+
+    for y in 0..image.height {
+        let row = &image.pixels[y * stride..(y + 1) * stride];
+        let above = if y > 0 { &image.pixels[(y - 1) * stride..y * stride] } else { &zero_row[..] };
+
+        let none = row.to_vec();
+        let sub: Vec<u8> = (0..stride).map(|i| row[i].wrapping_sub(if i >= 3 { row[i - 3] } else { 0 })).collect();
+        let up: Vec<u8> = row.iter().zip(above).map(|(&a, &b)| a.wrapping_sub(b)).collect();
+
+        let cost = |filtered: &[u8]| filtered.iter().map(|&b| (b as i8).unsigned_abs() as usize).sum::<usize>();
+        let (kind, best) = [(0u8, none), (1, sub), (2, up)]
+            .into_iter()
+            .min_by_key(|(_, filtered)| cost(filtered))
+            .unwrap();
+        out.push(kind);
+        out.extend_from_slice(&best);
+    }
+    out
+}
+
+/// Wrap deflated data in a zlib stream
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // CMF/FLG: deflate with a 32K window, no preset dictionary, check bits valid
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+const MAX_CHAIN: usize = 32;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] =
+    [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Deflate (RFC 1951) as one final block with the fixed Huffman codes, using
+/// greedy LZ77 matching over hash chains
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(1, 1); // final block
+    bits.write(1, 2); // fixed Huffman codes
+
+    let mut chains = HashChains::new(data);
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let limit = (data.len() - i).min(MAX_MATCH);
+            let mut candidate = chains.head[chains.hash(i)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > WINDOW - 1 {
+                    break;
+                }
+                let length = (0..limit).take_while(|&k| data[candidate + k] == data[i + k]).count();
+                if length > best.0 {
+                    best = (length, i - candidate);
+                    if length == limit {
+                        break;
+                    }
+                }
+                let next = chains.prev[candidate % WINDOW];
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+            }
+        }
+
+        if best.0 >= MIN_MATCH {
+            let (length, distance) = best;
+            write_length(&mut bits, length);
+            write_distance(&mut bits, distance);
+            for k in i..i + length {
+                chains.insert(k);
+            }
+            i += length;
+        } else {
+            write_literal(&mut bits, data[i] as u16);
+            chains.insert(i);
+            i += 1;
+        }
+    }
+
+    write_literal(&mut bits, 256); // end of block
+    bits.finish()
+}
+
+/// Most recent position of each 3-byte hash, and links from each position to
+/// the previous one with the same hash, within the last `WINDOW` bytes
+struct HashChains<'a> {
+    data: &'a [u8],
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl<'a> HashChains<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        HashChains { data, head: vec![usize::MAX; 1 << HASH_BITS], prev: vec![usize::MAX; WINDOW] }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        let key = (self.data[i] as u32) << 16 | (self.data[i + 1] as u32) << 8 | self.data[i + 2] as u32;
+        (key.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+    }
+
+    fn insert(&mut self, i: usize) {
+        if i + MIN_MATCH <= self.data.len() {
+            let h = self.hash(i);
+            self.prev[i % WINDOW] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+}
+
+/// Emit a literal/length symbol with its fixed Huffman code
+fn write_literal(bits: &mut BitWriter, symbol: u16) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    };
+    bits.write_code(code as u32, length);
+}
+
+fn write_length(bits: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASE.partition_point(|&base| base as usize <= length) - 1;
+    write_literal(bits, 257 + index as u16);
+    bits.write((length - LENGTH_BASE[index] as usize) as u32, LENGTH_EXTRA[index] as u32);
+}
+
+fn write_distance(bits: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASE.partition_point(|&base| base as usize <= distance) - 1;
+    bits.write_code(index as u32, 5);
+    bits.write((distance - DISTANCE_BASE[index] as usize) as u32, DISTANCE_EXTRA[index] as u32);
+}
+
+/// Packs values into bytes least significant bit first, as deflate requires
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are stored most significant bit first
+    fn write_code(&mut self, code: u32, length: u32) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::Shape;
+    use crate::render::{RasterPainter, Theme};
+    use crate::testutil::{build, options, shape_tests};
+
+    /// Reads bits least significant first, as deflate packs them
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn bit(&mut self) -> u32 {
+            let bit = (self.data[self.pos / 8] >> (self.pos % 8)) & 1;
+            self.pos += 1;
+            bit as u32
+        }
+
+        fn bits(&mut self, count: u32) -> u32 {
+            (0..count).map(|i| self.bit() << i).sum()
+        }
+
+        /// A fixed Huffman literal/length symbol; codes are read most significant bit first
+        fn symbol(&mut self) -> u32 {
+            let mut code = 0;
+            for length in 1..=9 {
+                code = code << 1 | self.bit();
+                match (length, code) {
+                    (7, 0..=23) => return 256 + code,
+                    (8, 0x30..=0xBF) => return code - 0x30,
+                    (8, 0xC0..=0xC7) => return 280 + code - 0xC0,
+                    (9, 0x190..=0x1FF) => return 144 + code - 0x190,
+                    _ => {}
+                }
+            }
+            panic!("invalid fixed Huffman code {:#b}", code);
+        }
+    }
+
+    /// Inflate a zlib stream of stored and fixed Huffman blocks, checking its
+    /// header and Adler-32 checksum. The length and distance tables are
+    /// rebuilt from RFC 1951's rules rather than taken from the encoder.
+    fn inflate(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(zlib[0] & 0x0F, 8, "not deflate");
+        assert_eq!((zlib[0] as u32 * 256 + zlib[1] as u32) % 31, 0, "bad header check bits");
+        let (mut lengths, mut distances) = (Vec::new(), Vec::new());
+        let (mut length, mut distance) = (3u32, 1u32);
+        for code in 0..29 {
+            let extra = if code < 8 { 0 } else { (code - 4) / 4 };
+            lengths.push((if code == 28 { 258 } else { length }, if code == 28 { 0 } else { extra }));
+            length += 1 << extra;
+        }
+        for code in 0..30 {
+            let extra = if code < 4 { 0 } else { (code - 2) / 2 };
+            distances.push((distance, extra));
+            distance += 1 << extra;
+        }
+
+        let mut bits = BitReader { data: &zlib[2..zlib.len() - 4], pos: 0 };
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let last = bits.bit() == 1;
+            match bits.bits(2) {
+                0 => {
+                    bits.pos = bits.pos.div_ceil(8) * 8;
+                    let (len, nlen) = (bits.bits(16), bits.bits(16));
+                    assert_eq!(len ^ 0xFFFF, nlen);
+                    out.extend((0..len).map(|_| bits.bits(8) as u8));
+                }
+                1 => loop {
+                    let symbol = bits.symbol();
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                        continue;
+                    } else if symbol == 256 {
+                        break;
+                    }
+                    let (base, extra) = lengths[symbol as usize - 257];
+                    let length = base + bits.bits(extra);
+                    let code = (0..5).fold(0, |code, _| code << 1 | bits.bit());
+                    let (base, extra) = distances[code as usize];
+                    let start = out.len() - (base + bits.bits(extra)) as usize;
+                    for k in 0..length as usize {
+                        out.push(out[start + k]);
+                    }
+                },
+                kind => panic!("unexpected block type {}", kind),
+            }
+            if last {
+                break;
+            }
+        }
+
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in &out {
+            a = (a + byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        assert_eq!(zlib[zlib.len() - 4..], ((b << 16) | a).to_be_bytes(), "bad Adler-32");
+        out
+    }
+
+    /// Undo the per-row filters, giving back the RGB pixels
+    fn unfilter(filtered: &[u8], width: usize) -> Vec<u8> {
+        let stride = width * 3;
+        let mut pixels: Vec<u8> = Vec::new();
+        for row in filtered.chunks(stride + 1) {
+            let start = pixels.len();
+            for (i, &byte) in row[1..].iter().enumerate() {
+                let predicted = match row[0] {
+                    0 => 0,
+                    1 if i >= 3 => pixels[start + i - 3],
+                    1 => 0,
+                    2 if start > 0 => pixels[start + i - stride],
+                    2 => 0,
+                    kind => panic!("unexpected filter type {}", kind),
+                };
+                pixels.push(byte.wrapping_add(predicted));
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_inflate_reads_stored_blocks() {
+        // Checks the test decoder itself on a block built by hand
+        let mut stream = vec![0x78, 0x01, 0x01, 0x03, 0x00, 0xFC, 0xFF];
+        stream.extend_from_slice(b"abc");
+        stream.extend_from_slice(&adler32(b"abc").to_be_bytes());
+        assert_eq!(inflate(&stream), b"abc");
+    }
+
+    #[test]
+    fn test_deflate_round_trip() {
+        // Literals on both sides of the 8/9 bit code split, runs up to the
+        // longest match, and matches from across the whole window
+        let mut noise = Vec::new();
+        let mut state = 7u32;
+        for _ in 0..5000 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            noise.push((state >> 16) as u8);
+        }
+        let mut far = noise.clone();
+        far.extend(vec![0u8; WINDOW - noise.len() - 10]);
+        far.extend_from_slice(&noise[..300]);
+        let cases: [Vec<u8>; 6] = [
+            Vec::new(),
+            b"a".to_vec(),
+            (0..=255).collect(),
+            vec![200; 1000],
+            noise,
+            far,
+        ];
+        for data in &cases {
+            assert_eq!(&inflate(&zlib(data)), data, "{} bytes", data.len());
+        }
+    }
+
+    /// The PNG's chunks, by type and body, checking every checksum
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let mut chunks = Vec::new();
+        let mut pos = 8;
+        while pos < png.len() {
+            let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + length];
+            let checksum = u32::from_be_bytes(png[pos + 8 + length..pos + 12 + length].try_into().unwrap());
+            assert_eq!(crc32(body), checksum);
+            chunks.push((String::from_utf8_lossy(&body[..4]).to_string(), body[4..].to_vec()));
+            pos += 12 + length;
+        }
+        chunks
+    }
+
+    fn test_png_chunks<S: Shape>() {
+        let maze = build::<S>(&options::<S>(7, 5, 9));
+        let solution = maze.solve();
+        let chunks = chunks(&S::to_png(&maze, 20, Some(&solution), true, &Theme::default(), 192.0).unwrap());
+        let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, ["IHDR", "pHYs", "tEXt", "IDAT", "IEND"]);
+        assert_eq!(chunks[2].1, b"Comment\0seed=9");
+    }
+
+    fn test_png_size_follows_dpi<S: Shape>() {
+        // Twice the SVG size at 192 DPI
+        let maze = build::<S>(&options::<S>(7, 5, 9));
+        let chunks = chunks(&S::to_png(&maze, 20, None, false, &Theme::default(), 192.0).unwrap());
+        let svg = S::to_svg(&maze, 20, None, false, &Theme::default());
+        let svg_width: f64 = svg.split("width=\"").nth(1).unwrap().split('"').next().unwrap().parse().unwrap();
+        let png_width = u32::from_be_bytes(chunks[0].1[0..4].try_into().unwrap());
+        assert_eq!(png_width, (svg_width * 2.0).ceil() as u32);
+
+        // Images too large to allocate are refused up front
+        assert!(S::to_png(&maze, 20, None, false, &Theme::default(), 1e6).is_err());
+    }
+
+    fn test_png_pixels_round_trip<S: Shape>() {
+        // The IDAT data inflates and unfilters back to the rendered pixels
+        let maze = build::<S>(&options::<S>(7, 5, 9));
+        let solution = maze.solve();
+        let mut painter = RasterPainter::new(96.0, &Theme::default());
+        S::draw(&maze, 20, Some(&solution), false, &mut painter);
+        let image = painter.finish();
+
+        let chunks = chunks(&encode(&image, 96.0, None));
+        let idat = &chunks.iter().find(|(kind, _)| kind == "IDAT").unwrap().1;
+        let filtered = inflate(idat);
+        assert_eq!(filtered, filter_rows(&image));
+        assert_eq!(unfilter(&filtered, image.width), image.pixels);
+    }
+
+    shape_tests!(test_png_chunks, test_png_size_follows_dpi, test_png_pixels_round_trip);
+}
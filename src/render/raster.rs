@@ -0,0 +1,351 @@
+use std::io;
+
+use super::{png, Painter, Point, Rgb, Theme, BASE_DPI};
+
+/// Largest image `RasterPainter` will allocate, in pixels. Stroking holds the
+/// RGB image and an `f32` coverage buffer, 7 bytes a pixel, so this caps the
+/// peak at about 940 MB; the filtered and compressed copies made while
+/// encoding are smaller for maze images.
+const MAX_PIXELS: f64 = (1u64 << 27) as f64;
+
+/// Vertical samples per pixel row when filling polygons; horizontal coverage is exact
+const POLYGON_SUBSAMPLES: usize = 4;
+
+/// How the ends of a stroked segment are drawn
+#[derive(Clone, Copy, PartialEq)]
+pub enum Cap {
+    /// Extend past each end by half the stroke width
+    Square,
+    /// Half-disc around each end
+    Round,
+}
+
+/// An RGB bitmap with anti-aliased drawing primitives
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGB triples
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Image { width, height, pixels: background.repeat(width * height) }
+    }
+
+    /// Mix `color` into a pixel with the given coverage (0.0..=1.0)
+    fn blend(&mut self, x: usize, y: usize, color: Rgb, coverage: f64) {
+        let offset = (y * self.width + x) * 3;
+        for (channel, &target) in self.pixels[offset..offset + 3].iter_mut().zip(&color) {
+            let mixed = *channel as f64 + (target as f64 - *channel as f64) * coverage;
+            *channel = mixed.round() as u8;
+        }
+    }
+
+    /// Pixel range covering `[min, max]` in one axis, clipped to `0..limit`
+    fn span(min: f64, max: f64, limit: usize) -> std::ops::Range<usize> {
+        let start = min.floor().max(0.0) as usize;
+        let end = (max.ceil().max(0.0) as usize).min(limit);
+        start..end.max(start)
+    }
+
+    /// Stroke a set of line segments in one pass. Coverage is combined per
+    /// pixel before blending, so overlapping segments (shared walls, joints in
+    /// a path) don't darken the anti-aliased edges where they meet.
+    pub fn stroke(&mut self, segments: &[(Point, Point)], width: f64, cap: Cap, color: Rgb) {
+        let half = width / 2.0;
+        let mut coverage = vec![0.0f32; self.width * self.height];
+
+        for &(from, to) in segments {
+            let reach = half + 1.0;
+            let xs = Self::span(from.0.min(to.0) - reach, from.0.max(to.0) + reach, self.width);
+            let ys = Self::span(from.1.min(to.1) - reach, from.1.max(to.1) + reach, self.height);
+            for y in ys {
+                for x in xs.clone() {
+                    let center = (x as f64 + 0.5, y as f64 + 0.5);
+                    let value = segment_coverage(center, from, to, half, cap) as f32;
+                    let cell = &mut coverage[y * self.width + x];
+                    *cell = cell.max(value);
+                }
+            }
+        }
+
+        for (i, &value) in coverage.iter().enumerate() {
+            if value > 0.0 {
+                self.blend(i % self.width, i / self.width, color, value as f64);
+            }
+        }
+    }
+
+    /// Fill a polygon (even-odd rule) with anti-aliased edges
+    pub fn fill_polygon(&mut self, points: &[Point], color: Rgb) {
+        if points.len() < 3 {
+            return;
+        }
+        let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let min_x = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        let xs = Self::span(min_x, max_x, self.width);
+        if xs.is_empty() {
+            return;
+        }
+
+        let mut row = vec![0.0; xs.len()];
+        for y in Self::span(min_y, max_y, self.height) {
+            row.iter_mut().for_each(|value| *value = 0.0);
+
+            for sample in 0..POLYGON_SUBSAMPLES {
+                let sample_y = y as f64 + (sample as f64 + 0.5) / POLYGON_SUBSAMPLES as f64;
+                let mut crossings: Vec<f64> = points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .filter(|(a, b)| (a.1 <= sample_y) != (b.1 <= sample_y))
+                    .map(|(a, b)| a.0 + (sample_y - a.1) / (b.1 - a.1) * (b.0 - a.0))
+                    .collect();
+                crossings.sort_by(f64::total_cmp);
+
+                for pair in crossings.chunks_exact(2) {
+                    let (left, right) = (pair[0].max(xs.start as f64), pair[1].min(xs.end as f64));
+                    for x in Self::span(left, right, xs.end) {
+                        let overlap = right.min(x as f64 + 1.0) - left.max(x as f64);
+                        if overlap > 0.0 {
+                            row[x - xs.start] += overlap / POLYGON_SUBSAMPLES as f64;
+                        }
+                    }
+                }
+            }
+
+            for (i, &value) in row.iter().enumerate() {
+                if value > 0.0 {
+                    self.blend(xs.start + i, y, color, value.min(1.0));
+                }
+            }
+        }
+    }
+
+    /// Draw digits centered on a point using a small built-in bitmap font.
+    /// Other characters leave a blank space.
+    pub fn text(&mut self, at: Point, size: f64, text: &str, color: Rgb) {
+        // Digits are 3x5 dots; `size` is the font size, of which digits take ~70%
+        let dot = size * 0.7 / 5.0;
+        let advance = dot * 4.0;
+        let total_width = advance * text.chars().count() as f64 - dot;
+        let left = at.0 - total_width / 2.0;
+        let top = at.1 - dot * 2.5;
+
+        for (i, c) in text.chars().enumerate() {
+            let Some(rows) = c.to_digit(10).map(|d| DIGITS[d as usize]) else { continue };
+            let glyph_left = left + advance * i as f64;
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        let x = glyph_left + dot * column as f64;
+                        let y = top + dot * row as f64;
+                        self.fill_polygon(&[(x, y), (x + dot, y), (x + dot, y + dot), (x, y + dot)], color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 3x5 bitmaps for the digits 0-9, one row per entry, high bit on the left
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Fraction of a pixel centered at `p` covered by a stroked segment, using
+/// the distance to the stroke's edge as a one-pixel-wide ramp
+fn segment_coverage(p: Point, from: Point, to: Point, half: f64, cap: Cap) -> f64 {
+    let ramp = |distance: f64| (half + 0.5 - distance).clamp(0.0, 1.0);
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    let (px, py) = (p.0 - from.0, p.1 - from.1);
+
+    if length == 0.0 {
+        return match cap {
+            Cap::Round => ramp((px * px + py * py).sqrt()),
+            Cap::Square => ramp(px.abs()).min(ramp(py.abs())),
+        };
+    }
+
+    // Position along the segment (from its midpoint) and across it
+    let (ux, uy) = (dx / length, dy / length);
+    let along = (px * ux + py * uy - length / 2.0).abs();
+    let across = (px * uy - py * ux).abs();
+
+    match cap {
+        Cap::Square => ramp(across) * ramp(along - length / 2.0),
+        Cap::Round => {
+            let beyond = (along - length / 2.0).max(0.0);
+            ramp((beyond * beyond + across * across).sqrt())
+        }
+    }
+}
+
+/// Split a polyline into the segments that are drawn under a dash pattern of
+/// alternating dash and gap lengths, as SVG's stroke-dasharray does. An empty
+/// pattern, or one that never advances along the line (negative lengths, or
+/// a total of zero), draws the whole line, as SVG does with such a pattern.
+fn dashed(points: &[Point], pattern: &[f64]) -> Vec<(Point, Point)> {
+    let segments = points.windows(2).map(|pair| (pair[0], pair[1]));
+    let total: f64 = pattern.iter().sum();
+    if pattern.iter().any(|&length| length < 0.0) || !(total > 0.0 && total.is_finite()) {
+        return segments.collect();
+    }
+
+    let mut dashes = Vec::new();
+    let (mut index, mut remaining, mut drawing) = (0, pattern[0], true);
+    for (from, to) in segments {
+        let length = (to.0 - from.0).hypot(to.1 - from.1);
+        let at = |distance: f64| {
+            let t = if length > 0.0 { distance / length } else { 0.0 };
+            (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+        };
+        let mut position = 0.0;
+        while length - position > remaining {
+            if drawing {
+                dashes.push((at(position), at(position + remaining)));
+            }
+            position += remaining;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+            drawing = !drawing;
+        }
+        if drawing {
+            dashes.push((at(position), to));
+        }
+        remaining -= length - position;
+    }
+    dashes
+}
+
+/// Painter producing a bitmap. Drawing units are scaled by `dpi / 96`, so the
+/// default resolution gives one pixel per SVG pixel.
+pub struct RasterPainter {
+    scale: f64,
+    theme: Theme,
+    image: Image,
+    fills: Vec<(Vec<Point>, Rgb)>,
+    walls: Vec<(Point, Point)>,
+    labels: Vec<(Point, f64, String)>,
+    solution: Vec<Point>,
+}
+
+impl RasterPainter {
+    pub fn new(dpi: f64, theme: &Theme) -> Self {
+        RasterPainter {
+            scale: dpi / BASE_DPI,
+            theme: theme.clone(),
+            image: Image::new(0, 0, theme.background.0),
+            fills: Vec::new(),
+            walls: Vec::new(),
+            labels: Vec::new(),
+            solution: Vec::new(),
+        }
+    }
+
+    /// Check that a drawing of the given size, in drawing units, fits in an
+    /// image at this resolution before anything is allocated for it
+    pub fn check_size(&self, width: f64, height: f64) -> io::Result<()> {
+        let (width, height) = self.scaled((width, height));
+        if width.ceil() * height.ceil() > MAX_PIXELS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:.0}x{:.0} pixel image is too large; lower the DPI or the maze size", width.ceil(), height.ceil()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn scaled(&self, p: Point) -> Point {
+        (p.0 * self.scale, p.1 * self.scale)
+    }
+
+    /// Rasterise everything drawn so far
+    pub fn finish(mut self) -> Image {
+        for (polygon, color) in &self.fills {
+            self.image.fill_polygon(polygon, *color);
+        }
+        let theme = &self.theme;
+        self.image.stroke(&self.walls, theme.wall_width * self.scale, Cap::Square, theme.wall_color.0);
+        for (at, size, text) in &self.labels {
+            self.image.text(*at, *size, text, theme.label_color.0);
+        }
+        let dash: Vec<f64> = theme.solution_dash.iter().map(|length| length * self.scale).collect();
+        let path = dashed(&self.solution, &dash);
+        self.image.stroke(&path, theme.solution_width * self.scale, Cap::Round, theme.solution_color.0);
+        self.image
+    }
+
+    /// Rasterise and encode as PNG, tagged with the resolution
+    pub fn finish_png(self, seed: Option<u64>) -> Vec<u8> {
+        let dpi = self.scale * BASE_DPI;
+        png::encode(&self.finish(), dpi, seed)
+    }
+}
+
+impl Painter for RasterPainter {
+    fn begin(&mut self, width: f64, height: f64) {
+        let (width, height) = self.scaled((width, height));
+        self.image = Image::new(width.ceil() as usize, height.ceil() as usize, self.theme.background.0);
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.walls.push((self.scaled(from), self.scaled(to)));
+    }
+
+    fn fill(&mut self, polygon: &[Point], color: Rgb) {
+        // Grow the polygon by half a pixel so the anti-aliased edges of
+        // neighbouring fills overlap instead of letting the background through
+        let points: Vec<Point> = polygon.iter().map(|&p| self.scaled(p)).collect();
+        let n = points.len() as f64;
+        let (cx, cy) = points.iter().fold((0.0, 0.0), |c, p| (c.0 + p.0 / n, c.1 + p.1 / n));
+        let grown = points
+            .iter()
+            .map(|&(x, y)| {
+                let length = (x - cx).hypot(y - cy).max(f64::MIN_POSITIVE);
+                (x + (x - cx) / length * 0.5, y + (y - cy) / length * 0.5)
+            })
+            .collect();
+        self.fills.push((grown, color));
+    }
+
+    fn label(&mut self, at: Point, font_size: f64, text: &str) {
+        self.labels.push((self.scaled(at), font_size * self.scale, text.to_string()));
+    }
+
+    fn solution(&mut self, points: &[Point]) {
+        self.solution = points.iter().map(|&p| self.scaled(p)).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashed() {
+        let line = [(0.0, 0.0), (10.0, 0.0), (10.0, 5.0)];
+        // Dashes carry on round corners
+        let dashes = [((0.0, 0.0), (4.0, 0.0)), ((7.0, 0.0), (10.0, 0.0)), ((10.0, 0.0), (10.0, 1.0)), ((10.0, 4.0), (10.0, 5.0))];
+        assert_eq!(dashed(&line, &[4.0, 3.0]), dashes);
+
+        // Patterns that would never move along the line draw it solid
+        let solid = dashed(&line, &[]);
+        assert_eq!(solid.len(), 2);
+        for pattern in [&[0.0][..], &[0.0, 0.0], &[-1.0, -1.0], &[3.0, -1.0], &[f64::NAN]] {
+            assert_eq!(dashed(&line, pattern), solid, "{:?}", pattern);
+        }
+    }
+}
@@ -0,0 +1,300 @@
+// STL output for 3D printing. The walls are extruded into one closed solid:
+// each wall becomes a slab along its centre line, and where walls meet their
+// sides are mitred together and the junction is filled, so the solid has no
+// internal faces or gaps. A rectangular base plate is a second closed solid
+// that the walls stand in, and the solution file adds a low ridge along the
+// solution path. Units are millimetres.
+use std::collections::HashMap;
+
+use super::plot::SegmentGraph;
+use super::{Painter, Point};
+
+/// A triangle with its corners listed counter-clockwise seen from outside
+type Triangle = [[f64; 3]; 3];
+
+/// Sizes of the printed maze, in millimetres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StlOptions {
+    /// Drawing units per inch
+    pub dpi: f64,
+    /// Height of the walls above the base plate
+    pub wall_height: f64,
+    pub wall_thickness: f64,
+    /// Thickness of the base plate; 0 for free-standing walls
+    pub base_thickness: f64,
+}
+
+/// Painter producing a binary STL model
+pub struct StlPainter {
+    options: StlOptions,
+    height: f64,
+    walls: Vec<(Point, Point)>,
+    solution: Vec<Point>,
+}
+
+impl StlPainter {
+    pub fn new(options: StlOptions) -> Self {
+        StlPainter { options, height: 0.0, walls: Vec::new(), solution: Vec::new() }
+    }
+
+    /// Convert to millimetres with y pointing up
+    fn mm(&self, (x, y): Point) -> Point {
+        let scale = 25.4 / self.options.dpi;
+        (x * scale, (self.height - y) * scale)
+    }
+
+    pub fn finish(self, seed: Option<u64>) -> Vec<u8> {
+        let StlOptions { wall_height, wall_thickness, base_thickness, .. } = self.options;
+        let mut triangles = Vec::new();
+
+        let walls: Vec<(Point, Point)> = self.walls.iter().map(|&(a, b)| (self.mm(a), self.mm(b))).collect();
+        let graph = SegmentGraph::new(&walls);
+        // Walls reach halfway into the plate rather than resting on it, so the
+        // two solids overlap instead of sharing a face
+        let bottom = base_thickness / 2.0;
+        extrude(&graph, wall_thickness, (bottom, base_thickness + wall_height), &mut triangles);
+
+        if self.solution.len() > 1 {
+            let path: Vec<(Point, Point)> =
+                self.solution.windows(2).map(|pair| (self.mm(pair[0]), self.mm(pair[1]))).collect();
+            let top = base_thickness + wall_height / 5.0;
+            extrude(&SegmentGraph::new(&path), wall_thickness, (bottom, top), &mut triangles);
+        }
+
+        if base_thickness > 0.0 && !graph.points.is_empty() {
+            // The plate covers the walls, including mitred corners that reach
+            // out up to a full thickness
+            let margin = wall_thickness;
+            let (min, max) = graph.points.iter().fold(
+                ((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY)),
+                |(min, max), p| ((min.0.min(p.0), min.1.min(p.1)), (max.0.max(p.0), max.1.max(p.1))),
+            );
+            let corners = [
+                (min.0 - margin, min.1 - margin),
+                (max.0 + margin, min.1 - margin),
+                (max.0 + margin, max.1 + margin),
+                (min.0 - margin, max.1 + margin),
+            ];
+            prism(&corners, (0.0, base_thickness), &mut triangles);
+        }
+
+        encode(&triangles, seed)
+    }
+}
+
+impl Painter for StlPainter {
+    fn begin(&mut self, _width: f64, height: f64) {
+        self.height = height;
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.walls.push((from, to));
+    }
+
+    fn label(&mut self, _at: Point, _font_size: f64, _text: &str) {}
+
+    fn solution(&mut self, points: &[Point]) {
+        self.solution = points.to_vec();
+    }
+}
+
+/// A horizontal polygon, given counter-clockwise seen from above, at heights
+/// `bottom` (facing down) and `top` (facing up)
+fn cap(polygon: [Point; 3], (bottom, top): (f64, f64), triangles: &mut Vec<Triangle>) {
+    let [a, b, c] = polygon;
+    triangles.push([[a.0, a.1, top], [b.0, b.1, top], [c.0, c.1, top]]);
+    triangles.push([[a.0, a.1, bottom], [c.0, c.1, bottom], [b.0, b.1, bottom]]);
+}
+
+/// Extrude a convex polygon, given counter-clockwise
+fn prism(polygon: &[Point], heights: (f64, f64), triangles: &mut Vec<Triangle>) {
+    for i in 1..polygon.len() - 1 {
+        cap([polygon[0], polygon[i], polygon[i + 1]], heights, triangles);
+    }
+    for (i, &a) in polygon.iter().enumerate() {
+        side(a, polygon[(i + 1) % polygon.len()], heights, triangles);
+    }
+}
+
+/// The vertical face along a boundary edge from `a` to `b`, with the solid on its left
+fn side(a: Point, b: Point, (bottom, top): (f64, f64), triangles: &mut Vec<Triangle>) {
+    triangles.push([[a.0, a.1, bottom], [b.0, b.1, bottom], [b.0, b.1, top]]);
+    triangles.push([[a.0, a.1, bottom], [b.0, b.1, top], [a.0, a.1, top]]);
+}
+
+/// Thicken a graph of segments into a solid between two heights. Around each
+/// point the sides of neighbouring segments are mitred together; a segment
+/// ending on its own gets a square cap half the thickness long.
+fn extrude(graph: &SegmentGraph, thickness: f64, heights: (f64, f64), triangles: &mut Vec<Triangle>) {
+    let half = thickness / 2.0;
+    let direction = |from: usize, to: usize| {
+        let (a, b) = (graph.points[from], graph.points[to]);
+        let length = (b.0 - a.0).hypot(b.1 - a.1);
+        ((b.0 - a.0) / length, (b.1 - a.1) / length)
+    };
+
+    // Corners where each segment leaves each of its points, (right, left)
+    // looking along the segment
+    let mut corners: HashMap<(usize, usize), (Point, Point)> = HashMap::new();
+    for (point, edges) in graph.adjacent.iter().enumerate() {
+        let v = graph.points[point];
+        let mut outgoing: Vec<(usize, Point)> =
+            edges.iter().map(|&edge| (edge, direction(point, graph.other_end(edge, point)))).collect();
+        outgoing.sort_by(|a, b| a.1.1.atan2(a.1.0).total_cmp(&b.1.1.atan2(b.1.0)));
+
+        if let [(edge, d)] = outgoing[..] {
+            let back = (v.0 - d.0 * half, v.1 - d.1 * half);
+            corners.insert((point, edge), ((back.0 + d.1 * half, back.1 - d.0 * half), (back.0 - d.1 * half, back.1 + d.0 * half)));
+            continue;
+        }
+
+        // Corner between each segment's left side and the next segment's right side, counter-clockwise
+        let mitres: Vec<Point> = (0..outgoing.len())
+            .map(|i| {
+                let (d1, d2) = (outgoing[i].1, outgoing[(i + 1) % outgoing.len()].1);
+                let p1 = (v.0 - d1.1 * half, v.1 + d1.0 * half);
+                let p2 = (v.0 + d2.1 * half, v.1 - d2.0 * half);
+                let denominator = d1.0 * d2.1 - d1.1 * d2.0;
+                if denominator.abs() < 1e-9 {
+                    // Straight through: the sides line up
+                    return p1;
+                }
+                let s = ((p2.0 - p1.0) * d2.1 - (p2.1 - p1.1) * d2.0) / denominator;
+                (p1.0 + d1.0 * s, p1.1 + d1.1 * s)
+            })
+            .collect();
+        for (i, &(edge, _)) in outgoing.iter().enumerate() {
+            let right = mitres[(i + outgoing.len() - 1) % outgoing.len()];
+            corners.insert((point, edge), (right, mitres[i]));
+        }
+
+        // Fill the junction between the ends of three or more segments
+        if outgoing.len() >= 3 {
+            for i in 0..mitres.len() {
+                cap([v, mitres[i], mitres[(i + 1) % mitres.len()]], heights, triangles);
+            }
+        }
+    }
+
+    for (edge, &(from, to)) in graph.edges.iter().enumerate() {
+        let (right_from, left_from) = corners[&(from, edge)];
+        let (right_to, left_to) = corners[&(to, edge)];
+        cap([right_from, left_to, right_to], heights, triangles);
+        cap([right_from, right_to, left_from], heights, triangles);
+        side(right_from, left_to, heights, triangles);
+        side(right_to, left_from, heights, triangles);
+
+        // Square caps on free ends
+        for (point, (right, left)) in [(from, (right_from, left_from)), (to, (right_to, left_to))] {
+            if graph.adjacent[point].len() == 1 {
+                side(left, right, heights, triangles);
+            }
+        }
+    }
+}
+
+/// Binary STL: an 80-byte header, the triangle count, then each triangle's
+/// normal and corners as little-endian floats
+fn encode(triangles: &[Triangle], seed: Option<u64>) -> Vec<u8> {
+    let mut header = seed.map(|seed| format!("maze seed={}", seed)).unwrap_or_else(|| "maze".to_string()).into_bytes();
+    header.resize(80, b' ');
+
+    let mut out = header;
+    out.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+    for [a, b, c] in triangles {
+        let (u, v) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+        let normal = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt().max(f64::MIN_POSITIVE);
+        for value in normal.iter().map(|n| n / length).chain(a.iter().chain(b).chain(c).copied()) {
+            out.extend_from_slice(&(value as f32).to_le_bytes());
+        }
+        out.extend_from_slice(&[0, 0]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::{GenericMaze, Shape};
+    use crate::testutil::{build, options, shape_tests};
+
+    type Vertex = [u32; 3];
+
+    // Triangles from a binary STL, with float bits as exact vertex keys
+    fn triangles(stl: &[u8]) -> Vec<[Vertex; 3]> {
+        let count = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+        assert_eq!(stl.len(), 84 + 50 * count);
+        let float = |at: usize| f32::from_le_bytes(stl[at..at + 4].try_into().unwrap()).to_bits();
+        (0..count)
+            .map(|i| {
+                let at = 84 + 50 * i + 12;
+                [0, 1, 2].map(|corner| [0, 1, 2].map(|axis| float(at + corner * 12 + axis * 4)))
+            })
+            .collect()
+    }
+
+    // Watertight and consistently oriented: every edge is shared by exactly
+    // two triangles, which run along it in opposite directions
+    fn check_closed(triangles: &[[Vertex; 3]]) {
+        let mut edges: HashMap<(Vertex, Vertex), usize> = HashMap::new();
+        for &[a, b, c] in triangles {
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_default() += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!((count, edges.get(&(b, a))), (1, Some(&1)));
+        }
+    }
+
+    fn volume(triangles: &[[Vertex; 3]]) -> f64 {
+        let f = |v: Vertex| v.map(|bits| f32::from_bits(bits) as f64);
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (f(a), f(b), f(c));
+                a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0]) + a[2] * (b[0] * c[1] - b[1] * c[0])
+            })
+            .sum::<f64>()
+            / 6.0
+    }
+
+    const OPTIONS: StlOptions = StlOptions { dpi: 96.0, wall_height: 10.0, wall_thickness: 1.5, base_thickness: 2.0 };
+
+    fn stl_maze<S: Shape>() -> GenericMaze<S> {
+        let mut options = options::<S>(9, 7, 4);
+        options.braid = 0.3;
+        build::<S>(&options)
+    }
+
+    fn test_stl_is_watertight<S: Shape>() {
+        let maze = stl_maze::<S>();
+        let stl = S::to_stl(&maze, 20, None, OPTIONS);
+        assert!(stl.starts_with(b"maze seed=4"));
+        let maze_only = triangles(&stl);
+        check_closed(&maze_only);
+
+        // The solution is raised on the plate as a closed solid of its own
+        let solved = triangles(&S::to_stl(&maze, 20, Some(&maze.solve()), OPTIONS));
+        check_closed(&solved);
+        assert!(volume(&solved) > volume(&maze_only) && volume(&maze_only) > 0.0);
+    }
+
+    fn test_stl_height<S: Shape>() {
+        // The model is as tall as the plate and walls together
+        let maze_only = triangles(&S::to_stl(&stl_maze::<S>(), 20, None, OPTIONS));
+        let top = maze_only.iter().flatten().map(|v| f32::from_bits(v[2])).fold(0.0, f32::max);
+        assert!((top - 12.0).abs() < 1e-4, "{} mm tall", top);
+    }
+
+    fn test_stl_without_base<S: Shape>() {
+        let maze = stl_maze::<S>();
+        let free_standing = StlOptions { base_thickness: 0.0, ..OPTIONS };
+        let walls = triangles(&S::to_stl(&maze, 20, None, free_standing));
+        check_closed(&walls);
+        assert!(volume(&walls) < volume(&triangles(&S::to_stl(&maze, 20, None, OPTIONS))));
+    }
+
+    shape_tests!(test_stl_is_watertight, test_stl_height, test_stl_without_base);
+}
@@ -0,0 +1,166 @@
+use std::f64::consts::PI;
+
+use super::{num, Arc, Color, Painter, Point, Rgb, Theme};
+
+/// The `<g>` element currently open, so consecutive items share their styling
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    None,
+    Fills,
+    Walls,
+    Labels(f64),
+}
+
+/// Painter producing an SVG document
+pub struct SvgPainter {
+    svg: String,
+    seed: Option<u64>,
+    theme: Theme,
+    group: Group,
+}
+
+impl SvgPainter {
+    /// Create a painter; the seed, if any, is recorded in the SVG metadata
+    pub fn new(seed: Option<u64>, theme: &Theme) -> Self {
+        SvgPainter { svg: String::new(), seed, theme: theme.clone(), group: Group::None }
+    }
+
+    /// Close the document and return it
+    pub fn finish(mut self) -> String {
+        self.open(Group::None);
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+
+    fn open(&mut self, group: Group) {
+        if self.group == group {
+            return;
+        }
+        if self.group != Group::None {
+            self.svg.push_str("  </g>\n");
+        }
+        match group {
+            Group::None => {}
+            // A thin outline in the fill colour covers the hairline gaps
+            // anti-aliasing leaves between neighbouring polygons
+            Group::Fills => self.svg.push_str("  <g stroke-width=\"0.5\" stroke-linejoin=\"round\">\n"),
+            Group::Walls => self.svg.push_str(&format!(
+                "  <g stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"square\" fill=\"none\">\n",
+                self.theme.wall_color,
+                num(self.theme.wall_width)
+            )),
+            Group::Labels(font_size) => self.svg.push_str(&format!(
+                "  <g font-family=\"{}\" font-size=\"{}\" text-anchor=\"middle\" fill=\"{}\">\n",
+                escape(&self.theme.label_font),
+                num(font_size),
+                self.theme.label_color
+            )),
+        }
+        self.group = group;
+    }
+}
+
+impl Painter for SvgPainter {
+    fn begin(&mut self, width: f64, height: f64) {
+        let (width, height) = (num(width), num(height));
+        self.svg.push_str(&format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
+"#,
+            width, height, width, height
+        ));
+        if let Some(seed) = self.seed {
+            self.svg.push_str(&format!("  <metadata>seed={}</metadata>\n", seed));
+        }
+        self.svg.push_str(&format!(
+            "  <rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            width, height, self.theme.background
+        ));
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.open(Group::Walls);
+        self.svg.push_str(&format!(
+            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+            num(from.0),
+            num(from.1),
+            num(to.0),
+            num(to.1)
+        ));
+    }
+
+    fn arc_wall(&mut self, arc: &Arc) {
+        self.open(Group::Walls);
+        let ((x1, y1), (x2, y2)) = (arc.point(arc.start), arc.point(arc.end));
+        // Sweep flag 1 runs the arc towards growing angles, clockwise on screen
+        let large_arc = u8::from(arc.end - arc.start > PI);
+        self.svg.push_str(&format!(
+            "    <path d=\"M {} {} A {} {} 0 {} 1 {} {}\"/>\n",
+            num(x1),
+            num(y1),
+            num(arc.radius),
+            num(arc.radius),
+            large_arc,
+            num(x2),
+            num(y2)
+        ));
+    }
+
+    fn arc_border(&mut self, arc: &Arc) {
+        self.arc_wall(arc);
+    }
+
+    fn fill(&mut self, polygon: &[Point], color: Rgb) {
+        self.open(Group::Fills);
+        let points: Vec<String> = polygon.iter().map(|&(x, y)| format!("{},{}", num(x), num(y))).collect();
+        let color = Color(color);
+        self.svg.push_str(&format!(
+            "    <polygon points=\"{}\" fill=\"{}\" stroke=\"{}\"/>\n",
+            points.join(" "),
+            color,
+            color
+        ));
+    }
+
+    fn label(&mut self, at: Point, font_size: f64, text: &str) {
+        self.open(Group::Labels(font_size));
+        // SVG positions text by its baseline; drop it by a third of the font size to center it
+        self.svg.push_str(&format!(
+            "    <text x=\"{}\" y=\"{}\">{}</text>\n",
+            num(at.0),
+            num(at.1 + font_size / 3.0),
+            escape(text)
+        ));
+    }
+
+    fn solution(&mut self, points: &[Point]) {
+        if points.is_empty() {
+            return;
+        }
+        self.open(Group::None);
+        let dash = if self.theme.solution_dash.is_empty() {
+            String::new()
+        } else {
+            let lengths: Vec<String> = self.theme.solution_dash.iter().map(|&length| num(length)).collect();
+            format!(" stroke-dasharray=\"{}\"", lengths.join(" "))
+        };
+        self.svg.push_str(&format!(
+            "  <g stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\"{} fill=\"none\">\n",
+            self.theme.solution_color,
+            num(self.theme.solution_width),
+            dash
+        ));
+        self.svg.push_str("    <path class=\"solution-path\" d=\"");
+        for (i, &(x, y)) in points.iter().enumerate() {
+            let command = if i == 0 { "M" } else { "L" };
+            self.svg.push_str(&format!("{} {} {} ", command, num(x), num(y)));
+        }
+        self.svg.push_str("\"/>\n");
+        self.svg.push_str("  </g>\n");
+    }
+}
+
+/// Escape text for use in XML content and double-quoted attributes
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::{Painter, Point};
+
+/// Character set used by the text renderers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextStyle {
+    /// Plain ASCII: `+`, `-`, `|`, with `*` marking the solution
+    Ascii,
+    /// Unicode box-drawing characters, with `░` marking the solution
+    Unicode,
+}
+
+impl TextStyle {
+    /// Character used to trace the solution path
+    pub fn solution_mark(self) -> char {
+        match self {
+            TextStyle::Ascii => '*',
+            TextStyle::Unicode => '░',
+        }
+    }
+
+    /// Characters for rising and falling diagonal walls
+    pub fn diagonals(self) -> (char, char) {
+        match self {
+            TextStyle::Ascii => ('/', '\\'),
+            TextStyle::Unicode => ('╱', '╲'),
+        }
+    }
+}
+
+impl FromStr for TextStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ascii" => Ok(TextStyle::Ascii),
+            "unicode" => Ok(TextStyle::Unicode),
+            _ => Err(format!("unknown text style '{}', expected ascii or unicode", s)),
+        }
+    }
+}
+
+/// Box-drawing character joining wall segments in the given directions
+pub fn box_junction(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (true, true, false, false) => '│',
+        (false, false, true, true) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+/// Inverse of `box_junction`: the `(up, down, left, right)` directions a
+/// box-drawing character joins, or `None` if it isn't one
+pub fn junction_arms(c: char) -> Option<(bool, bool, bool, bool)> {
+    if c == ' ' {
+        return None;
+    }
+    (0..16u8)
+        .map(|bits| (bits & 1 != 0, bits & 2 != 0, bits & 4 != 0, bits & 8 != 0))
+        .find(|&(up, down, left, right)| box_junction(up, down, left, right) == c)
+}
+
+/// A fixed-size grid of characters that text renderers draw into
+pub struct CharGrid {
+    rows: Vec<Vec<char>>,
+}
+
+impl CharGrid {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        CharGrid { rows: vec![vec![' '; columns]; rows] }
+    }
+
+    pub fn get(&self, column: usize, row: usize) -> char {
+        self.rows[row][column]
+    }
+
+    pub fn set(&mut self, column: usize, row: usize, c: char) {
+        self.rows[row][column] = c;
+    }
+
+    /// The grid as lines of text, with trailing spaces removed
+    pub fn finish(&self) -> String {
+        let mut text = String::new();
+        for row in &self.rows {
+            let line: String = row.iter().collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// What has been traced through one character of a `TextPainter`
+#[derive(Debug, Clone, Copy, Default)]
+struct Trace {
+    /// Directions a horizontal or vertical wall leaves the character in:
+    /// up, down, left, right
+    arms: [bool; 4],
+    diagonal: Option<char>,
+    solution: bool,
+}
+
+/// Painter tracing a drawing onto a character grid, for shapes without a
+/// text layout of their own. Each character covers `CHAR_WIDTH` by
+/// `CHAR_HEIGHT` drawing units, as tall as two wide like the characters
+/// themselves. Walls become horizontal, vertical or diagonal strokes by
+/// their direction, and the solution is traced between the cell centers in
+/// the space the walls leave free.
+pub struct TextPainter {
+    style: TextStyle,
+    traces: HashMap<(i64, i64), Trace>,
+}
+
+impl TextPainter {
+    /// Tunnel width to draw at, so that the narrowest tunnels of any shape
+    /// are a few characters across
+    pub const TUNNEL_WIDTH: usize = 24;
+    const CHAR_WIDTH: f64 = 4.0;
+    const CHAR_HEIGHT: f64 = 8.0;
+
+    pub fn new(style: TextStyle) -> Self {
+        TextPainter { style, traces: HashMap::new() }
+    }
+
+    /// Character grid position of a point, as (column, row)
+    fn position((x, y): Point) -> (f64, f64) {
+        (x / Self::CHAR_WIDTH, y / Self::CHAR_HEIGHT)
+    }
+
+    /// Characters along a line, stepping one column or row at a time along
+    /// whichever the line covers more of
+    fn line(from: Point, to: Point) -> Vec<(i64, i64)> {
+        let ((c0, r0), (c1, r1)) = (Self::position(from), Self::position(to));
+        let steps = (c1.round() - c0.round()).abs().max((r1.round() - r0.round()).abs()) as i64;
+        (0..=steps)
+            .map(|step| {
+                let t = if steps == 0 { 0.0 } else { step as f64 / steps as f64 };
+                ((c0 + (c1 - c0) * t).round() as i64, (r0 + (r1 - r0) * t).round() as i64)
+            })
+            .collect()
+    }
+
+    /// The traced drawing, trimmed to the characters in use
+    pub fn finish(&self) -> String {
+        let Some(&first) = self.traces.keys().next() else { return String::new() };
+        let (min, max) = self.traces.keys().fold((first, first), |(min, max), &(column, row)| {
+            ((min.0.min(column), min.1.min(row)), (max.0.max(column), max.1.max(row)))
+        });
+
+        let (horizontal, vertical) = match self.style {
+            TextStyle::Ascii => ('-', '|'),
+            TextStyle::Unicode => ('─', '│'),
+        };
+        let mut grid = CharGrid::new((max.0 - min.0 + 1) as usize, (max.1 - min.1 + 1) as usize);
+        for (&(column, row), trace) in &self.traces {
+            let [up, down, left, right] = trace.arms;
+            let c = match (up || down, left || right) {
+                (true, true) => match self.style {
+                    TextStyle::Ascii => '+',
+                    TextStyle::Unicode => box_junction(up, down, left, right),
+                },
+                (false, true) => horizontal,
+                (true, false) => vertical,
+                (false, false) => match trace.diagonal {
+                    Some(diagonal) => diagonal,
+                    None if trace.solution => self.style.solution_mark(),
+                    None => ' ',
+                },
+            };
+            grid.set((column - min.0) as usize, (row - min.1) as usize, c);
+        }
+        grid.finish()
+    }
+}
+
+impl Painter for TextPainter {
+    fn begin(&mut self, _width: f64, _height: f64) {}
+
+    fn wall(&mut self, from: Point, to: Point) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        // Steeper than this is vertical, shallower than its inverse horizontal
+        let steep = 30f64.to_radians().tan().recip();
+        let cells = Self::line(from, to);
+        let last = cells.len() - 1;
+        for (i, &cell) in cells.iter().enumerate() {
+            let trace = self.traces.entry(cell).or_default();
+            // A stroke within one character still crosses it
+            let (before, after) = (i > 0 || last == 0, i < last || last == 0);
+            if dy.abs() > dx.abs() * steep {
+                let (towards_from, towards_to) = if dy > 0.0 { (0, 1) } else { (1, 0) };
+                trace.arms[towards_from] |= before;
+                trace.arms[towards_to] |= after;
+            } else if dx.abs() > dy.abs() * steep {
+                let (towards_from, towards_to) = if dx > 0.0 { (2, 3) } else { (3, 2) };
+                trace.arms[towards_from] |= before;
+                trace.arms[towards_to] |= after;
+            } else {
+                // y grows downwards, so a line rising to the right has dx and dy of opposite signs
+                let (rising, falling) = self.style.diagonals();
+                trace.diagonal = Some(if (dx > 0.0) != (dy > 0.0) { rising } else { falling });
+            }
+        }
+    }
+
+    fn label(&mut self, _at: Point, _font_size: f64, _text: &str) {}
+
+    fn solution(&mut self, points: &[Point]) {
+        for pair in points.windows(2) {
+            for cell in Self::line(pair[0], pair[1]) {
+                self.traces.entry(cell).or_default().solution = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::{GenericMaze, Shape};
+    use crate::shapes::{HexShape, RectShape, TriShape};
+    use crate::testutil::{build, options, shape_tests};
+
+    #[test]
+    fn test_text_output() {
+        // The snake from test_stats_on_known_maze: 0 -> 1 -> 2 -> 5 -> 4 -> 3
+        let mut maze = GenericMaze::<RectShape>::new(3, 2);
+        maze.carve(0, 2);
+        maze.carve(1, 2);
+        maze.carve(2, 1);
+        maze.carve(5, 3);
+        maze.carve(4, 3);
+        maze.exit = 3;
+        let solution = maze.solve();
+
+        let ascii = RectShape::to_text(&maze, Some(&solution), TextStyle::Ascii);
+        assert_eq!(
+            ascii,
+            "+  +--+--+\n\
+             |********|\n\
+             +--+--+**+\n\
+             |********|\n\
+             +  +--+--+\n"
+        );
+
+        let unicode = RectShape::to_text(&maze, None, TextStyle::Unicode);
+        assert_eq!(
+            unicode,
+            "╷  ╶─────┐\n\
+             │        │\n\
+             ├─────╴  │\n\
+             │        │\n\
+             ╵  ╶─────┘\n"
+        );
+
+        // Uncarved hex and triangle grids, open only at the entrance and exit
+        let hex = GenericMaze::<HexShape>::new(2, 2);
+        assert_eq!(
+            HexShape::to_text(&hex, None, TextStyle::Ascii),
+            " __\n\
+             \x20  \\__\n\
+             \\__/  \\\n\
+             /  \\__/\n\
+             \\__/  \\\n\
+             \x20  \\__\n"
+        );
+        let tri = GenericMaze::<TriShape>::new(3, 2);
+        assert_eq!(
+            TriShape::to_text(&tri, None, TextStyle::Ascii),
+            "    ____\n\
+             \x20  \\    /\\\n\
+             \x20   \\  /  \\\n\
+             \x20____\\/____\\\n\
+             \\    /\\\n\
+             \x20\\  /  \\\n\
+             \x20 \\/____\\\n"
+        );
+    }
+
+    fn test_text_marks_solution<S: Shape>() {
+        // Every solution cell is marked, and nothing is without a solution
+        let maze = build::<S>(&options::<S>(9, 7, 8));
+        let solution = maze.solve();
+        let text = S::to_text(&maze, Some(&solution), TextStyle::Ascii);
+        assert!(text.matches('*').count() >= solution.len() * 2);
+        assert!(!S::to_text(&maze, None, TextStyle::Unicode).contains('░'));
+    }
+
+    shape_tests!(test_text_marks_solution);
+}
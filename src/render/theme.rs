@@ -0,0 +1,242 @@
+// Colours and stroke widths shared by the SVG, PNG, PDF and plotter
+// backends. A theme starts from the defaults below and can be loaded from a
+// JSON file, e.g. {"wall_color": "#333", "solution_dash": [6, 4]}, with any
+// field left out keeping its default.
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use super::Rgb;
+
+/// Colour names accepted on the command line and in theme files, also used
+/// when writing SVG so the default output keeps its familiar names
+const NAMED_COLORS: [(&str, Rgb); 12] = [
+    ("black", [0, 0, 0]),
+    ("white", [255, 255, 255]),
+    ("red", [255, 0, 0]),
+    ("green", [0, 128, 0]),
+    ("blue", [0, 0, 255]),
+    ("yellow", [255, 255, 0]),
+    ("orange", [255, 165, 0]),
+    ("purple", [128, 0, 128]),
+    ("navy", [0, 0, 128]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("silver", [192, 192, 192]),
+];
+
+/// Heatmap colours from near to far, pale yellow through green to dark blue, dark
+/// enough at the far end to stay distinct from white but light enough that
+/// black walls and the red solution still stand out
+const RAMP: [Rgb; 5] = [[255, 255, 204], [161, 218, 180], [65, 182, 196], [44, 127, 184], [37, 52, 148]];
+
+/// A colour given by name or as #rgb / #rrggbb
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Color(pub Rgb);
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        if let Some(&(_, rgb)) = NAMED_COLORS.iter().find(|(name, _)| *name == lower) {
+            return Ok(Color(rgb));
+        }
+        let invalid = || format!("unknown colour '{}', expected a name such as black or a hex value such as #ff8800", s);
+        let hex = lower.strip_prefix('#').ok_or_else(invalid)?;
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>().ok_or_else(invalid)?;
+        match digits[..] {
+            [r, g, b] => Ok(Color([r * 17, g * 17, b * 17])),
+            [r1, r2, g1, g2, b1, b2] => Ok(Color([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2])),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Color {
+    /// The colour's name if it has one, otherwise #rrggbb
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match NAMED_COLORS.iter().find(|(_, rgb)| *rgb == self.0) {
+            Some((name, _)) => write!(f, "{}", name),
+            None => write!(f, "#{:02x}{:02x}{:02x}", self.0[0], self.0[1], self.0[2]),
+        }
+    }
+}
+
+/// What each cell is filled with, underneath the walls
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum CellFill {
+    None,
+    Solid(Color),
+    /// Shade cells by their distance from the entrance
+    Heatmap,
+}
+
+impl FromStr for CellFill {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(CellFill::None),
+            "heatmap" => Ok(CellFill::Heatmap),
+            _ => s
+                .parse()
+                .map(CellFill::Solid)
+                .map_err(|_| format!("unknown cell fill '{}', expected none, heatmap or a colour", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for CellFill {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// How a maze drawing looks. Widths are in drawing units.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub background: Color,
+    pub wall_color: Color,
+    pub wall_width: f64,
+    pub solution_color: Color,
+    pub solution_width: f64,
+    /// Alternating dash and gap lengths for the solution; empty for a solid line
+    pub solution_dash: Vec<f64>,
+    pub label_color: Color,
+    /// Font family for debug labels in SVG output; PDF uses Courier and PNG
+    /// its built-in digits
+    pub label_font: String,
+    pub cell_fill: CellFill,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: Color([255, 255, 255]),
+            wall_color: Color([0, 0, 0]),
+            wall_width: 2.0,
+            solution_color: Color([255, 0, 0]),
+            solution_width: 3.0,
+            solution_dash: Vec::new(),
+            label_color: Color([0, 0, 255]),
+            label_font: "monospace".to_string(),
+            cell_fill: CellFill::None,
+        }
+    }
+}
+
+/// Colour for a position along the heatmap ramp, from 0.0 (nearest) to 1.0 (farthest)
+pub fn heat_color(fraction: f64) -> Rgb {
+    let position = fraction.clamp(0.0, 1.0) * (RAMP.len() - 1) as f64;
+    let index = (position as usize).min(RAMP.len() - 2);
+    let t = position - index as f64;
+    let (from, to) = (RAMP[index], RAMP[index + 1]);
+    std::array::from_fn(|channel| (from[channel] as f64 + (to[channel] as f64 - from[channel] as f64) * t).round() as u8)
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Theme {
+    /// Parse a theme file, rejecting unknown fields and widths that can't be drawn
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let theme: Theme = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+        if !positive(theme.wall_width) || !positive(theme.solution_width) {
+            return Err(invalid("wall and solution widths must be positive".to_string()));
+        }
+        if !theme.solution_dash.iter().all(|&length| positive(length)) {
+            return Err(invalid("solution dash lengths must be positive".to_string()));
+        }
+        Ok(theme)
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genericmaze::Shape;
+    use crate::render::{write_pdf, PageSize};
+    use crate::shapes::RectShape;
+    use crate::testutil::{build, options};
+
+    #[test]
+    fn test_theme() {
+        assert_eq!("navy".parse::<Color>().unwrap(), Color([0, 0, 128]));
+        assert_eq!("#F80".parse::<Color>().unwrap(), Color([255, 136, 0]));
+        assert_eq!("#0a0b0c".parse::<Color>().unwrap(), Color([10, 11, 12]));
+        for bad in ["", "#12", "#ggg", "0a0b0c", "mauve"] {
+            assert!(bad.parse::<Color>().is_err(), "accepted {:?}", bad);
+        }
+        assert_eq!(Color([255, 0, 0]).to_string(), "red");
+        assert_eq!(Color([1, 2, 3]).to_string(), "#010203");
+        assert_eq!("Heatmap".parse::<CellFill>().unwrap(), CellFill::Heatmap);
+        assert_eq!("#eee".parse::<CellFill>().unwrap(), CellFill::Solid(Color([238, 238, 238])));
+        assert!("stripes".parse::<CellFill>().is_err());
+
+        // Theme files set only what they mention
+        let theme = Theme::from_json(r##"{"wall_color": "#333", "solution_dash": [6, 4], "cell_fill": "silver"}"##).unwrap();
+        assert_eq!(theme.wall_color, Color([51, 51, 51]));
+        assert_eq!(theme.solution_dash, [6.0, 4.0]);
+        assert_eq!(theme.cell_fill, CellFill::Solid(Color([192, 192, 192])));
+        assert_eq!(theme.wall_width, Theme::default().wall_width);
+        for bad in [r#"{"wall_colour": "red"}"#, r#"{"wall_width": 0}"#, r#"{"solution_dash": [3, -1]}"#, r#"{"background": "clear"}"#] {
+            assert!(Theme::from_json(bad).is_err(), "accepted {}", bad);
+        }
+
+        let theme = Theme::from_json(
+            r#"{"wall_color": "green", "wall_width": 1.5, "background": "silver", "solution_dash": [2, 3], "label_font": "serif"}"#,
+        )
+        .unwrap();
+        let maze = build::<RectShape>(&options::<RectShape>(8, 6, 2));
+        let solution = maze.solve();
+        let svg = RectShape::to_svg(&maze, 20, Some(&solution), true, &theme);
+        assert!(svg.contains(r#"<rect width="#) && svg.contains(r#"fill="silver"/>"#));
+        assert!(svg.contains(r#"<g stroke="green" stroke-width="1.5""#));
+        assert!(svg.contains(r#"stroke-dasharray="2 3""#));
+        assert!(svg.contains(r#"font-family="serif""#));
+        let quoted = Theme { label_font: r#""Fira" & <Mono>"#.to_string(), ..theme.clone() };
+        let svg = RectShape::to_svg(&maze, 20, None, true, &quoted);
+        assert!(svg.contains(r#"font-family="&quot;Fira&quot; &amp; &lt;Mono&gt;""#));
+
+        // The defaults keep the classic look
+        let plain = RectShape::to_svg(&maze, 20, Some(&solution), false, &Theme::default());
+        assert!(plain.contains(r#"<g stroke="black" stroke-width="2""#));
+        assert!(plain.contains(r#"<g stroke="red" stroke-width="3" stroke-linecap="round" fill="none">"#));
+
+        let page = RectShape::pdf_page(&maze, 20, Some(&solution), false, &theme);
+        let pdf = String::from_utf8_lossy(&write_pdf(&[page], PageSize::A4, 0.0, None)).to_string();
+        assert!(pdf.contains("0.753 0.753 0.753 rg 0 0 "));
+        assert!(pdf.contains("0 0.502 0 RG 1.5 w 2 J"));
+        assert!(pdf.contains("1 0 0 RG 3 w 1 J 1 j [2 3] 0 d"));
+
+        // Dashing leaves gaps in the raster solution
+        let solid = Theme { solution_dash: Vec::new(), ..theme.clone() };
+        let png = |theme: &Theme| RectShape::to_png(&maze, 20, Some(&solution), false, theme, 96.0).unwrap();
+        assert_ne!(png(&theme), png(&solid));
+
+        let plot = RectShape::to_plot(&maze, 20, Some(&solution)).to_svg(None, &theme);
+        assert!(plot.contains(r#"<g id="walls" stroke="green" stroke-width="1.5""#));
+    }
+}
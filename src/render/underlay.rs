@@ -0,0 +1,124 @@
+// Cell fills, such as distance heatmaps, drawn underneath the walls, labels
+// and solution of any shape.
+use super::{Arc, Painter, Point, Rgb};
+
+/// Painter wrapper that fills the given polygons as soon as the drawing
+/// begins, so everything else is drawn on top of them
+pub struct Underlay<'a, P: Painter> {
+    painter: &'a mut P,
+    fills: Vec<(Vec<Point>, Rgb)>,
+}
+
+impl<'a, P: Painter> Underlay<'a, P> {
+    pub fn new(painter: &'a mut P, fills: Vec<(Vec<Point>, Rgb)>) -> Self {
+        Underlay { painter, fills }
+    }
+}
+
+impl<P: Painter> Painter for Underlay<'_, P> {
+    fn begin(&mut self, width: f64, height: f64) {
+        self.painter.begin(width, height);
+        for (polygon, color) in std::mem::take(&mut self.fills) {
+            self.painter.fill(&polygon, color);
+        }
+    }
+
+    fn wall(&mut self, from: Point, to: Point) {
+        self.painter.wall(from, to);
+    }
+
+    fn border(&mut self, from: Point, to: Point) {
+        self.painter.border(from, to);
+    }
+
+    fn arc_wall(&mut self, arc: &Arc) {
+        self.painter.arc_wall(arc);
+    }
+
+    fn arc_border(&mut self, arc: &Arc) {
+        self.painter.arc_border(arc);
+    }
+
+    fn fill(&mut self, polygon: &[Point], color: Rgb) {
+        self.painter.fill(polygon, color);
+    }
+
+    fn label(&mut self, at: Point, font_size: f64, text: &str) {
+        self.painter.label(at, font_size, text);
+    }
+
+    fn solution(&mut self, points: &[Point]) {
+        self.painter.solution(points);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::genericmaze::{GenericMaze, Shape};
+    use crate::render::{heat_color, write_pdf, CellFill, PageSize, Theme};
+    use crate::testutil::{build, options, shape_tests};
+
+    #[test]
+    fn test_heat_color() {
+        assert_eq!(heat_color(0.0), [255, 255, 204]);
+        assert_eq!(heat_color(1.0), [37, 52, 148]);
+        assert_eq!(heat_color(2.0), heat_color(1.0));
+    }
+
+    fn heatmap_theme() -> Theme {
+        Theme { cell_fill: CellFill::Heatmap, ..Theme::default() }
+    }
+
+    fn heatmap_maze<S: Shape>() -> GenericMaze<S> {
+        build::<S>(&options::<S>(12, 9, 6))
+    }
+
+    fn test_heatmap_fills_under_walls<S: Shape>() {
+        let maze = heatmap_maze::<S>();
+        let plain = S::to_svg(&maze, 20, None, false, &Theme::default());
+        let svg = S::to_svg(&maze, 20, None, false, &heatmap_theme());
+
+        // One fill per cell, all underneath the walls
+        assert_eq!(svg.matches("<polygon").count(), maze.cells.len());
+        assert!(svg.rfind("<polygon").unwrap() < svg.find("<line").unwrap());
+        assert!(!plain.contains("<polygon"));
+
+        // Apart from the fills, the drawing is unchanged
+        let fills = svg.find("  <g stroke-width=\"0.5\"").unwrap();
+        let end = fills + svg[fills..].find("  </g>\n").unwrap() + "  </g>\n".len();
+        assert_eq!(format!("{}{}", &svg[..fills], &svg[end..]), plain);
+    }
+
+    fn test_heatmap_ramp<S: Shape>() {
+        // The entrance is the start of the ramp and the farthest cell its end
+        let maze = heatmap_maze::<S>();
+        let distances = maze.distances_from(maze.entrance);
+        let farthest = (0..maze.cells.len()).max_by_key(|&idx| distances[idx]).unwrap();
+        let heatmap = maze.heatmap(20);
+        assert_eq!(heatmap[maze.entrance].1, heat_color(0.0));
+        assert_eq!(heatmap[farthest].1, heat_color(1.0));
+    }
+
+    fn test_heatmap_follows_cell_outlines<S: Shape>() {
+        // Each fill is the cell's outline, with a corner per wall where the walls are straight
+        let maze = heatmap_maze::<S>();
+        for (idx, (polygon, _)) in maze.heatmap(20).iter().enumerate() {
+            assert_eq!(polygon, &S::cell_polygon(&maze, 20, idx), "cell {}", idx);
+            let walls = maze.cells[idx].walls.len();
+            if (0..walls).all(|wall_idx| S::wall_arc(&maze, 20, idx, wall_idx).is_none()) {
+                assert_eq!(polygon.len(), walls, "cell {}", idx);
+            }
+        }
+    }
+
+    fn test_heatmap_in_png_and_pdf<S: Shape>() {
+        let maze = heatmap_maze::<S>();
+        let png = |theme: &Theme| S::to_png(&maze, 20, None, false, theme, 96.0).unwrap();
+        assert_ne!(png(&heatmap_theme()), png(&Theme::default()));
+        let page = S::pdf_page(&maze, 20, None, false, &heatmap_theme());
+        let pdf = write_pdf(&[page], PageSize::A4, 0.0, None);
+        assert_eq!(String::from_utf8_lossy(&pdf).matches("h B").count(), maze.cells.len());
+    }
+
+    shape_tests!(test_heatmap_fills_under_walls, test_heatmap_ramp, test_heatmap_follows_cell_outlines, test_heatmap_in_png_and_pdf);
+}
@@ -1,11 +1,16 @@
 use crate::genericmaze::{cell_index, GenericMaze, MazeCell, Shape};
+use crate::render::{CharGrid, Point, TextStyle};
 
 /// Hexagonal grid shape (6 neighbors: N, S, NE, SE, NW, SW)
 pub struct HexShape;
 
 impl Shape for HexShape {
-    fn num_neighbors() -> usize {
-        6
+    fn name() -> &'static str {
+        "hexagonal"
+    }
+
+    fn edge_labels(_width: usize, _height: usize, _x: usize, _y: usize) -> &'static [&'static str] {
+        &["N", "S", "NE", "SE", "NW", "SW"]
     }
 
     fn init_neighbors(width: usize, height: usize, cells: &mut [MazeCell]) {
@@ -67,133 +72,124 @@ impl Shape for HexShape {
         }
     }
 
-    fn to_svg(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool) -> String {
-        let hex_width = tunnel_width;
-        let hex_height = (tunnel_width as f64 * 0.866).round() as usize;
-        let svg_width = maze.width * hex_width * 3 / 4 + hex_width / 4 + 10;
-        let svg_height = maze.height * hex_height + hex_height / 2 + 10;
-
-        let mut svg = String::new();
-        svg.push_str(&format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
-  <rect width="{}" height="{}" fill="white"/>
-  <g stroke="black" stroke-width="2" stroke-linecap="square" fill="none">
-"#,
-            svg_width, svg_height, svg_width, svg_height, svg_width, svg_height
-        ));
-
-        let hex_center = |x: usize, y: usize| -> (usize, usize) {
-            let cx = x * hex_width * 3 / 4 + hex_width / 2;
-            let cy = y * hex_height + if x % 2 == 1 { hex_height / 2 } else { 0 } + hex_height / 2;
-            (cx, cy)
-        };
-
-        // Draw hexagons and walls
+    fn boundary_walls(maze: &GenericMaze<Self>, idx: usize) -> Vec<usize> {
+        // Prefer the NW and SE edges so corner openings sit on the diagonal
+        [4, 3, 0, 1, 2, 5]
+            .into_iter()
+            .filter(|&wall_idx| maze.cells[idx].neighbors[wall_idx].is_none())
+            .collect()
+    }
+
+    fn drawing_size(maze: &GenericMaze<Self>, tunnel_width: usize) -> (f64, f64) {
+        let hex_width = tunnel_width as f64;
+        let hex_height = (hex_width * 0.866).round();
+        let width = maze.width as f64 * hex_width * 3.0 / 4.0 + hex_width / 4.0 + 10.0;
+        let height = maze.height as f64 * hex_height + hex_height / 2.0 + 10.0;
+        (width, height)
+    }
+
+    fn cell_polygon(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Vec<Point> {
+        let (cx, cy) = Self::cell_center(maze, tunnel_width, idx);
+        let w = tunnel_width as f64 / 2.0;
+        let h = (tunnel_width as f64 * 0.866).round() / 2.0;
+        vec![
+            (cx - w/2.0, cy - h),   // 0: top-left (NW corner)
+            (cx + w/2.0, cy - h),   // 1: top-right (NE corner)
+            (cx + w, cy),           // 2: right (E corner)
+            (cx + w/2.0, cy + h),   // 3: bottom-right (SE corner)
+            (cx - w/2.0, cy + h),   // 4: bottom-left (SW corner)
+            (cx - w, cy),           // 5: left (W corner)
+        ]
+    }
+
+    fn cell_center(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Point {
+        // Odd columns sit half a row lower
+        let (x, y) = maze.cell_coords(idx);
+        let hex_width = tunnel_width as f64;
+        let hex_height = (hex_width * 0.866).round();
+        let cx = x as f64 * hex_width * 3.0 / 4.0 + hex_width / 2.0;
+        let cy = y as f64 * hex_height + if x % 2 == 1 { hex_height / 2.0 } else { 0.0 } + hex_height / 2.0;
+        (cx, cy)
+    }
+
+    fn wall_corners(_maze: &GenericMaze<Self>, _idx: usize, wall_idx: usize) -> Option<(usize, usize)> {
+        // Polygon corners of each wall, in neighbor index order
+        let edges = [
+            (0, 1), // 0: N edge (top)
+            (4, 3), // 1: S edge (bottom)
+            (1, 2), // 2: NE edge
+            (2, 3), // 3: SE edge
+            (5, 0), // 4: NW edge
+            (4, 5), // 5: SW edge
+        ];
+        edges.get(wall_idx).copied()
+    }
+
+    fn to_text(maze: &GenericMaze<Self>, solution_path: Option<&[usize]>, style: TextStyle) -> String {
+        // Each hexagon spans four columns and three rows, sharing its
+        // diagonal columns and underscore rows with its neighbors:
+        //  __
+        // /  \__
+        // \__/  \
+        //    \__/
+        let (rising, falling) = style.diagonals();
+        let offset_rows = if maze.width > 1 { 1 } else { 0 };
+        let mut grid = CharGrid::new(maze.width * 3 + 1, maze.height * 2 + 1 + offset_rows);
+        let top_left = |x: usize, y: usize| (x * 3, y * 2 + x % 2);
+
         for y in 0..maze.height {
             for x in 0..maze.width {
                 let idx = maze.cell_index(x, y);
-                let (cx, cy) = hex_center(x, y);
-
-                let w = hex_width / 2;
-                let h = hex_height / 2;
-
-                let points = [
-                    (cx - w/2, cy - h),     // 0: top-left (NW corner)
-                    (cx + w/2, cy - h),     // 1: top-right (NE corner)
-                    (cx + w, cy),           // 2: right (E corner)
-                    (cx + w/2, cy + h),     // 3: bottom-right (SE corner)
-                    (cx - w/2, cy + h),     // 4: bottom-left (SW corner)
-                    (cx - w, cy),           // 5: left (W corner)
-                ];
-
-                // Draw each edge if wall exists (matching neighbor indices: N, S, NE, SE, NW, SW)
-                let edges = [
-                    (0, 1), // 0: N edge (top)
-                    (4, 3), // 1: S edge (bottom)
-                    (1, 2), // 2: NE edge
-                    (2, 3), // 3: SE edge
-                    (5, 0), // 4: NW edge
-                    (4, 5), // 5: SW edge
+                let (column, row) = top_left(x, y);
+
+                // Character cells of each edge, in neighbor index order: N, S, NE, SE, NW, SW
+                let edges: [&[(usize, usize, char)]; 6] = [
+                    &[(column + 1, row, '_'), (column + 2, row, '_')],
+                    &[(column + 1, row + 2, '_'), (column + 2, row + 2, '_')],
+                    &[(column + 3, row + 1, falling)],
+                    &[(column + 3, row + 2, rising)],
+                    &[(column, row + 1, rising)],
+                    &[(column, row + 2, falling)],
                 ];
-
-                for (wall_idx, &(p1, p2)) in edges.iter().enumerate() {
-                    if maze.cells[idx].walls[wall_idx] {
-                        // Skip entrance (NW edge of cell 0) and exit (SE edge of last cell)
-                        let is_entrance = idx == 0 && wall_idx == 4; // NW edge of cell 0
-                        let is_exit = idx == maze.cells.len() - 1 && wall_idx == 3; // SE edge of last cell
-
-                        if !is_entrance && !is_exit {
-                            svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
-                                points[p1].0, points[p1].1, points[p2].0, points[p2].1));
+                for (wall_idx, chars) in edges.iter().enumerate() {
+                    if maze.cells[idx].walls[wall_idx] && !maze.is_opening(idx, wall_idx) {
+                        for &(c, r, ch) in chars.iter() {
+                            grid.set(c, r, ch);
                         }
                     }
                 }
             }
         }
 
-        svg.push_str("  </g>\n");
-
-        // Add cell index labels for debugging
-        if debug {
-            svg.push_str("  <g font-family=\"monospace\" font-size=\"12\" text-anchor=\"middle\" fill=\"blue\">\n");
-            for y in 0..maze.height {
-                for x in 0..maze.width {
-                    let idx = maze.cell_index(x, y);
-                    let (cx, cy) = hex_center(x, y);
-                    svg.push_str(&format!("    <text x=\"{}\" y=\"{}\">{}</text>\n", cx, cy + 4, idx));
-                }
-            }
-            svg.push_str("  </g>\n");
-        }
-
+        // Fill the solution cells and the open edges between consecutive ones
         if let Some(path) = solution_path {
-            if !path.is_empty() {
-                svg.push_str("  <g stroke=\"red\" stroke-width=\"3\" stroke-linecap=\"round\" fill=\"none\">\n");
-                svg.push_str("    <path class=\"solution-path\" d=\"");
-
-                for (i, &idx) in path.iter().enumerate() {
+            let mark = style.solution_mark();
+            for &idx in path {
+                let (column, row) = {
                     let (x, y) = maze.cell_coords(idx);
-                    let (cx, cy) = hex_center(x, y);
-
-                    if i == 0 {
-                        svg.push_str(&format!("M {} {} ", cx, cy));
-                    } else {
-                        svg.push_str(&format!("L {} {} ", cx, cy));
-                    }
-                }
-
-                svg.push_str("\"/>\n");
-                svg.push_str("  </g>\n");
+                    top_left(x, y)
+                };
+                grid.set(column + 1, row + 1, mark);
+                grid.set(column + 2, row + 1, mark);
             }
-        }
-
-        svg.push_str("</svg>");
-        svg
-    }
-
-    fn print_debug_info(maze: &GenericMaze<Self>) {
-        println!("\n=== Hexagonal Maze Debug Info ===");
-        println!("Grid: {}x{} (width x height)", maze.width, maze.height);
-        println!("Total cells: {}", maze.cells.len());
-        println!("\nNeighbor relationships (indices: N, S, NE, SE, NW, SW):");
-
-        for y in 0..maze.height {
-            for x in 0..maze.width {
-                let idx = maze.cell_index(x, y);
-                let is_odd_col = x % 2 == 1;
-                print!("Cell {:2} (x={}, y={}, {}): [",
-                    idx, x, y, if is_odd_col { "odd " } else { "even" });
-
-                for (i, &neighbor) in maze.cells[idx].neighbors.iter().enumerate() {
-                    if i > 0 { print!(", "); }
-                    match neighbor {
-                        Some(n) => print!("{:2}", n),
-                        None => print!("--"),
-                    }
+            for pair in path.windows(2) {
+                let Some(edge) = maze.cells[pair[0]].neighbors.iter().position(|&n| n == Some(pair[1])) else { continue };
+                let (x, y) = maze.cell_coords(pair[0]);
+                let (column, row) = top_left(x, y);
+                let gap: &[(usize, usize)] = match edge {
+                    0 => &[(column + 1, row), (column + 2, row)],
+                    1 => &[(column + 1, row + 2), (column + 2, row + 2)],
+                    2 => &[(column + 3, row + 1)],
+                    3 => &[(column + 3, row + 2)],
+                    4 => &[(column, row + 1)],
+                    _ => &[(column, row + 2)],
+                };
+                for &(c, r) in gap {
+                    grid.set(c, r, mark);
                 }
-                println!("]");
             }
         }
+
+        grid.finish()
     }
 }
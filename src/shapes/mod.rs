@@ -3,8 +3,10 @@ mod rect_shape;
 mod tri_shape;
 mod hex_shape;
 mod oct_shape;
+mod polar_shape;
 
 pub use rect_shape::RectShape;
 pub use tri_shape::TriShape;
 pub use hex_shape::HexShape;
 pub use oct_shape::OctShape;
+pub use polar_shape::PolarShape;
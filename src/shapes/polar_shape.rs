@@ -0,0 +1,232 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use crate::genericmaze::{GenericMaze, MazeCell, Shape};
+use crate::render::{Arc, Point};
+
+/// Circular grid of concentric rings around an open hub (4 or 5 neighbors:
+/// inward, clockwise, counter-clockwise and one or two outward). The width
+/// is the number of cells in the innermost ring and the height the number
+/// of rings; rows are rings, and x counts cells clockwise from 12 o'clock.
+pub struct PolarShape;
+
+/// Fewest cells in a ring, so each cell's clockwise and counter-clockwise
+/// neighbors are different cells
+const MIN_RING_LEN: usize = 3;
+
+/// Space around the outermost ring, in drawing units
+const MARGIN: f64 = 10.0;
+
+/// Whether cells of `ring` would be more than 1.5 tunnel widths wide around
+/// the middle of the ring if it held `len` cells, so it splits every cell in two
+fn too_wide(ring: usize, len: usize) -> bool {
+    TAU * (ring as f64 + 1.5) / len as f64 > 1.5
+}
+
+/// Where a cell lies: its ring's radii and the angles it spans, clockwise
+struct Sector {
+    center: Point,
+    inner: f64,
+    outer: f64,
+    start: f64,
+    end: f64,
+}
+
+impl Sector {
+    fn of(maze: &GenericMaze<PolarShape>, tunnel_width: usize, idx: usize) -> Self {
+        let (x, ring) = maze.cell_coords(idx);
+        let (radius, step) = (tunnel_width as f64, TAU / maze.row_len(ring) as f64);
+        // The hub inside the first ring has a radius of one tunnel width
+        let edge = (maze.height + 1) as f64 * radius + MARGIN;
+        Sector {
+            center: (edge, edge),
+            inner: (ring + 1) as f64 * radius,
+            outer: (ring + 2) as f64 * radius,
+            start: x as f64 * step - FRAC_PI_2,
+            end: (x + 1) as f64 * step - FRAC_PI_2,
+        }
+    }
+
+    fn arc(&self, radius: f64, start: f64, end: f64) -> Arc {
+        Arc { center: self.center, radius, start, end }
+    }
+
+    fn point(&self, radius: f64, angle: f64) -> Point {
+        (self.center.0 + radius * angle.cos(), self.center.1 + radius * angle.sin())
+    }
+}
+
+impl Shape for PolarShape {
+    fn name() -> &'static str {
+        "polar"
+    }
+
+    fn edge_labels(width: usize, height: usize, _x: usize, y: usize) -> &'static [&'static str] {
+        // Two outward edges where the next ring splits the cell in two
+        if y + 1 < height && Self::row_len(width, y + 1) > Self::row_len(width, y) {
+            &["in", "cw", "ccw", "out", "out"]
+        } else {
+            &["in", "cw", "ccw", "out"]
+        }
+    }
+
+    fn row_len(width: usize, y: usize) -> usize {
+        // Jump from one doubling to the next rather than stepping ring by
+        // ring, since this is called for every cell
+        let (mut len, mut ring) = (width.max(MIN_RING_LEN), 1);
+        loop {
+            let estimate = (1.5 * len as f64 / TAU - 1.5).floor().max(0.0) as usize + 1;
+            let mut next = estimate.max(ring);
+            while next > ring && too_wide(next - 1, len) {
+                next -= 1;
+            }
+            while !too_wide(next, len) {
+                next += 1;
+            }
+            if next > y {
+                return len;
+            }
+            len *= 2;
+            ring = next + 1;
+        }
+    }
+
+    fn init_neighbors(width: usize, height: usize, cells: &mut [MazeCell]) {
+        // Neighbor indices: 0=inward, 1=clockwise, 2=counter-clockwise,
+        // 3=outward (the first half, if the next ring splits the cell), 4=outward second half
+        let lens: Vec<usize> = (0..height).map(|ring| Self::row_len(width, ring)).collect();
+        let mut starts = vec![0];
+        for len in &lens {
+            starts.push(starts[starts.len() - 1] + len);
+        }
+
+        for ring in 0..height {
+            let len = lens[ring];
+            for x in 0..len {
+                let idx = starts[ring] + x;
+                if ring > 0 {
+                    cells[idx].neighbors[0] = Some(starts[ring - 1] + x / (len / lens[ring - 1]));
+                }
+                cells[idx].neighbors[1] = Some(starts[ring] + (x + 1) % len);
+                cells[idx].neighbors[2] = Some(starts[ring] + (x + len - 1) % len);
+                if ring + 1 < height {
+                    let split = lens[ring + 1] / len;
+                    for half in 0..split {
+                        cells[idx].neighbors[3 + half] = Some(starts[ring + 1] + x * split + half);
+                    }
+                }
+            }
+        }
+    }
+
+    fn boundary_walls(maze: &GenericMaze<Self>, idx: usize) -> Vec<usize> {
+        // Out of the maze from the outermost ring, into the hub from the innermost
+        let (_, ring) = maze.cell_coords(idx);
+        let mut walls = Vec::new();
+        if ring == maze.height - 1 {
+            walls.push(3);
+        }
+        if ring == 0 {
+            walls.push(0);
+        }
+        walls
+    }
+
+    fn drawing_size(maze: &GenericMaze<Self>, tunnel_width: usize) -> (f64, f64) {
+        let size = 2.0 * ((maze.height + 1) as f64 * tunnel_width as f64 + MARGIN);
+        (size, size)
+    }
+
+    fn cell_polygon(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Vec<Point> {
+        // Clockwise along the outer walls, then back along the inner one, so
+        // the outline passes through the corner between split outer walls
+        let sector = Sector::of(maze, tunnel_width, idx);
+        let mut points = Vec::new();
+        for wall_idx in [3, 4] {
+            if let Some(arc) = Self::wall_arc(maze, tunnel_width, idx, wall_idx) {
+                points.pop();
+                points.extend(arc.points());
+            }
+        }
+        points.extend(sector.arc(sector.inner, sector.start, sector.end).points().into_iter().rev());
+        points
+    }
+
+    fn cell_center(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Point {
+        let sector = Sector::of(maze, tunnel_width, idx);
+        sector.point((sector.inner + sector.outer) / 2.0, (sector.start + sector.end) / 2.0)
+    }
+
+    fn wall_segment(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize, wall_idx: usize) -> Option<(Point, Point)> {
+        let sector = Sector::of(maze, tunnel_width, idx);
+        match wall_idx {
+            1 => Some((sector.point(sector.inner, sector.end), sector.point(sector.outer, sector.end))),
+            2 => Some((sector.point(sector.inner, sector.start), sector.point(sector.outer, sector.start))),
+            _ => Self::wall_arc(maze, tunnel_width, idx, wall_idx).map(|arc| (arc.point(arc.start), arc.point(arc.end))),
+        }
+    }
+
+    fn wall_arc(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize, wall_idx: usize) -> Option<Arc> {
+        let sector = Sector::of(maze, tunnel_width, idx);
+        let split = maze.cells[idx].walls.len() == 5;
+        let middle = (sector.start + sector.end) / 2.0;
+        match wall_idx {
+            0 => Some(sector.arc(sector.inner, sector.start, sector.end)),
+            3 if split => Some(sector.arc(sector.outer, sector.start, middle)),
+            3 => Some(sector.arc(sector.outer, sector.start, sector.end)),
+            4 if split => Some(sector.arc(sector.outer, middle, sector.end)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoints::{place_endpoints, Endpoint, Side};
+    use crate::render::{TextStyle, Theme};
+    use crate::testutil::{build, options};
+
+    #[test]
+    fn test_polar_grid() {
+        // Rings double once their cells grow too wide, and never hold fewer than three
+        let maze = GenericMaze::<PolarShape>::new(6, 6);
+        let rings: Vec<usize> = (0..maze.height).map(|ring| maze.row_len(ring)).collect();
+        assert_eq!(rings, [6, 12, 24, 24, 24, 48]);
+        assert_eq!(maze.cells.len(), rings.iter().sum::<usize>());
+        assert_eq!(maze.cell_coords(maze.cell_index(30, 5)), (30, 5));
+        assert_eq!(GenericMaze::<PolarShape>::new(1, 2).cells.len(), 3 + 6);
+        for width in 1..20 {
+            let mut len = width.max(MIN_RING_LEN);
+            for ring in 0..300 {
+                if ring > 0 && too_wide(ring, len) {
+                    len *= 2;
+                }
+                assert_eq!(PolarShape::row_len(width, ring), len);
+            }
+        }
+
+        // Inward from a split cell's second half, and back out again
+        let outer = maze.cell_index(7, 2);
+        let inner = maze.cells[outer].neighbors[0].unwrap();
+        assert_eq!(maze.cell_coords(inner), (3, 1));
+        assert_eq!(maze.cells[inner].neighbors[3..], [Some(outer - 1), Some(outer)]);
+        assert_eq!(maze.edge_labels(inner), ["in", "cw", "ccw", "out", "out"]);
+        assert_eq!(maze.edge_labels(maze.cell_index(0, 3)), ["in", "cw", "ccw", "out"]);
+
+        // Openings lead into the hub and out of the outer ring
+        let mut options = options::<PolarShape>(6, 6, 19);
+        options.entrance = Some(Endpoint::Side(Side::North));
+        options.exit = Some(Endpoint::Cell(40, 5));
+        let mut maze = build::<PolarShape>(&options);
+        assert_eq!((maze.opening_wall(maze.entrance), maze.cell_coords(maze.exit)), (Some(0), (40, 5)));
+        assert!(place_endpoints(&mut maze, Some(Endpoint::Cell(6, 0)), None).is_err());
+
+        // SVG draws curved walls as arcs; other backends get them as short lines
+        let solution = maze.solve();
+        let svg = PolarShape::to_svg(&maze, 20, Some(&solution), false, &Theme::default());
+        assert!(svg.contains(" A 20 20 0 0 1 ") && svg.contains(" A 140 140 0 0 1 ") && svg.contains("<line"));
+        let plot = PolarShape::to_plot(&maze, 20, None);
+        assert!(plot.paths.iter().all(|path| path.iter().all(|&(x, y)| (x - 150.0).hypot(y - 150.0) <= 140.0 + 1e-6)));
+        assert!(PolarShape::to_text(&maze, Some(&solution), TextStyle::Unicode).contains('░'));
+    }
+}
@@ -1,11 +1,20 @@
 use crate::genericmaze::{cell_index, GenericMaze, MazeCell, Shape};
+use crate::render::{box_junction, CharGrid, Point, TextStyle};
+
+/// Space between neighbouring tunnels, and around the grid, left for the
+/// walls; themes with wider walls draw over the tunnels as on other grids
+const WALL_SPACING: f64 = 2.0;
 
 /// Rectangular grid shape (4 neighbors: N, S, E, W)
 pub struct RectShape;
 
 impl Shape for RectShape {
-    fn num_neighbors() -> usize {
-        4
+    fn name() -> &'static str {
+        "rectangular"
+    }
+
+    fn edge_labels(_width: usize, _height: usize, _x: usize, _y: usize) -> &'static [&'static str] {
+        &["N", "S", "E", "W"]
     }
 
     fn init_neighbors(width: usize, height: usize, cells: &mut [MazeCell]) {
@@ -37,142 +46,110 @@ impl Shape for RectShape {
         }
     }
 
-    fn to_svg(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool) -> String {
-        let wall_thickness = 2;
-        let cell_size = tunnel_width + wall_thickness;
-        let svg_width = maze.width * cell_size + wall_thickness;
-        let svg_height = maze.height * cell_size + wall_thickness;
-
-        let mut svg = String::new();
-        svg.push_str(&format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
-  <rect width="{}" height="{}" fill="white"/>
-  <g stroke="black" stroke-width="{}" stroke-linecap="square">
-"#,
-            svg_width, svg_height, svg_width, svg_height, svg_width, svg_height, wall_thickness
-        ));
+    fn boundary_walls(maze: &GenericMaze<Self>, idx: usize) -> Vec<usize> {
+        // Prefer openings in the top and bottom edges, as in the classic layout
+        [0, 1, 3, 2]
+            .into_iter()
+            .filter(|&wall_idx| maze.cells[idx].neighbors[wall_idx].is_none())
+            .collect()
+    }
+
+    fn drawing_size(maze: &GenericMaze<Self>, tunnel_width: usize) -> (f64, f64) {
+        let cell_size = tunnel_width as f64 + WALL_SPACING;
+        (maze.width as f64 * cell_size + WALL_SPACING, maze.height as f64 * cell_size + WALL_SPACING)
+    }
+
+    fn cell_polygon(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Vec<Point> {
+        let cell_size = tunnel_width as f64 + WALL_SPACING;
+        let (x, y) = maze.cell_coords(idx);
+        let cell_x = x as f64 * cell_size + WALL_SPACING;
+        let cell_y = y as f64 * cell_size + WALL_SPACING;
+        // Clockwise from the top-left corner
+        vec![
+            (cell_x, cell_y),
+            (cell_x + cell_size, cell_y),
+            (cell_x + cell_size, cell_y + cell_size),
+            (cell_x, cell_y + cell_size),
+        ]
+    }
+
+    fn cell_center(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Point {
+        let cell_size = tunnel_width as f64 + WALL_SPACING;
+        let (x, y) = maze.cell_coords(idx);
+        (
+            x as f64 * cell_size + WALL_SPACING + cell_size / 2.0,
+            y as f64 * cell_size + WALL_SPACING + cell_size / 2.0,
+        )
+    }
+
+    fn wall_corners(_maze: &GenericMaze<Self>, _idx: usize, wall_idx: usize) -> Option<(usize, usize)> {
+        // Polygon corners of each wall, in neighbor index order: N, S, E, W
+        [(0, 1), (3, 2), (1, 2), (0, 3)].get(wall_idx).copied()
+    }
+
+    fn to_text(maze: &GenericMaze<Self>, solution_path: Option<&[usize]>, style: TextStyle) -> String {
+        // Each cell is two characters wide between corner columns, and one
+        // row tall between corner rows: "+--+" / "|  |"
+        let mut grid = CharGrid::new(maze.width * 3 + 1, maze.height * 2 + 1);
+        let (horizontal, vertical) = match style {
+            TextStyle::Ascii => ('-', '|'),
+            TextStyle::Unicode => ('─', '│'),
+        };
 
         for y in 0..maze.height {
             for x in 0..maze.width {
                 let idx = maze.cell_index(x, y);
-                let cell_x = x * cell_size + wall_thickness;
-                let cell_y = y * cell_size + wall_thickness;
-
-                // North wall (index 0)
-                if maze.cells[idx].walls[0] {
-                    if idx != 0 {  // Entrance is north wall of cell 0
-                        svg.push_str(&format!(
-                            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
-                            cell_x,
-                            cell_y,
-                            cell_x + cell_size,
-                            cell_y
-                        ));
-                    }
-                }
+                let has_wall = |wall_idx: usize| maze.cells[idx].walls[wall_idx] && !maze.is_opening(idx, wall_idx);
+                let (column, row) = (x * 3, y * 2);
 
-                // South wall (index 1)
-                if maze.cells[idx].walls[1] {
-                    if idx != maze.cells.len() - 1 {  // Exit is south wall of last cell
-                        svg.push_str(&format!(
-                            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
-                            cell_x,
-                            cell_y + cell_size,
-                            cell_x + cell_size,
-                            cell_y + cell_size
-                        ));
+                for (wall_idx, top_row) in [(0, row), (1, row + 2)] {
+                    if has_wall(wall_idx) {
+                        grid.set(column + 1, top_row, horizontal);
+                        grid.set(column + 2, top_row, horizontal);
                     }
                 }
-
-                // East wall (index 2)
-                if maze.cells[idx].walls[2] {
-                    svg.push_str(&format!(
-                        "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
-                        cell_x + cell_size,
-                        cell_y,
-                        cell_x + cell_size,
-                        cell_y + cell_size
-                    ));
-                }
-
-                // West wall (index 3)
-                if maze.cells[idx].walls[3] {
-                    if idx != 0 {  // Entrance is west wall of cell 0
-                        svg.push_str(&format!(
-                            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
-                            cell_x,
-                            cell_y,
-                            cell_x,
-                            cell_y + cell_size
-                        ));
+                for (wall_idx, wall_column) in [(2, column + 3), (3, column)] {
+                    if has_wall(wall_idx) {
+                        grid.set(wall_column, row + 1, vertical);
                     }
                 }
             }
         }
 
-        svg.push_str("  </g>\n");
-
-        // Add cell index labels for debugging
-        if debug {
-            svg.push_str("  <g font-family=\"monospace\" font-size=\"12\" text-anchor=\"middle\" fill=\"blue\">\n");
-            for y in 0..maze.height {
-                for x in 0..maze.width {
-                    let idx = maze.cell_index(x, y);
-                    let center_x = x * cell_size + wall_thickness + cell_size / 2;
-                    let center_y = y * cell_size + wall_thickness + cell_size / 2;
-                    svg.push_str(&format!("    <text x=\"{}\" y=\"{}\">{}</text>\n", center_x, center_y + 4, idx));
-                }
+        for row in (0..=maze.height * 2).step_by(2) {
+            for column in (0..=maze.width * 3).step_by(3) {
+                let corner = match style {
+                    TextStyle::Ascii => '+',
+                    TextStyle::Unicode => box_junction(
+                        row > 0 && grid.get(column, row - 1) == vertical,
+                        row < maze.height * 2 && grid.get(column, row + 1) == vertical,
+                        column > 0 && grid.get(column - 1, row) == horizontal,
+                        column < maze.width * 3 && grid.get(column + 1, row) == horizontal,
+                    ),
+                };
+                grid.set(column, row, corner);
             }
-            svg.push_str("  </g>\n");
         }
 
+        // Fill the solution cells and the gaps between consecutive ones
         if let Some(path) = solution_path {
-            if !path.is_empty() {
-                svg.push_str("  <g stroke=\"red\" stroke-width=\"3\" stroke-linecap=\"round\" fill=\"none\">\n");
-                svg.push_str("    <path class=\"solution-path\" d=\"");
-
-                for (i, &idx) in path.iter().enumerate() {
-                    let (x, y) = maze.cell_coords(idx);
-                    let center_x = x * cell_size + wall_thickness + cell_size / 2;
-                    let center_y = y * cell_size + wall_thickness + cell_size / 2;
-
-                    if i == 0 {
-                        svg.push_str(&format!("M {} {} ", center_x, center_y));
-                    } else {
-                        svg.push_str(&format!("L {} {} ", center_x, center_y));
-                    }
-                }
-
-                svg.push_str("\"/>\n");
-                svg.push_str("  </g>\n");
+            let mark = style.solution_mark();
+            for &idx in path {
+                let (x, y) = maze.cell_coords(idx);
+                grid.set(x * 3 + 1, y * 2 + 1, mark);
+                grid.set(x * 3 + 2, y * 2 + 1, mark);
             }
-        }
-
-        svg.push_str("</svg>");
-        svg
-    }
-
-    fn print_debug_info(maze: &GenericMaze<Self>) {
-        println!("\n=== Rectangular Maze Debug Info ===");
-        println!("Grid: {}x{} (width x height)", maze.width, maze.height);
-        println!("Total cells: {}", maze.cells.len());
-        println!("\nNeighbor relationships (indices: 0=N, 1=S, 2=E, 3=W):");
-
-        for y in 0..maze.height {
-            for x in 0..maze.width {
-                let idx = maze.cell_index(x, y);
-                print!("Cell {:2} (x={}, y={}): [", idx, x, y);
-
-                for (i, &neighbor) in maze.cells[idx].neighbors.iter().enumerate() {
-                    if i > 0 { print!(", "); }
-                    match neighbor {
-                        Some(n) => print!("{:2}", n),
-                        None => print!("--"),
-                    }
+            for pair in path.windows(2) {
+                let ((x1, y1), (x2, y2)) = (maze.cell_coords(pair[0]), maze.cell_coords(pair[1]));
+                if y1 == y2 {
+                    grid.set(x1.max(x2) * 3, y1 * 2 + 1, mark);
+                } else {
+                    grid.set(x1 * 3 + 1, y1.max(y2) * 2, mark);
+                    grid.set(x1 * 3 + 2, y1.max(y2) * 2, mark);
                 }
-                println!("]");
             }
         }
+
+        grid.finish()
     }
 }
@@ -1,11 +1,20 @@
 use crate::genericmaze::{cell_index, GenericMaze, MazeCell, Shape};
+use crate::render::{CharGrid, Point, TextStyle};
 
 /// Triangular grid shape (3 neighbors: left, right, top/bottom)
 pub struct TriShape;
 
 impl Shape for TriShape {
-    fn num_neighbors() -> usize {
-        3
+    fn name() -> &'static str {
+        "triangular"
+    }
+
+    fn edge_labels(_width: usize, _height: usize, x: usize, y: usize) -> &'static [&'static str] {
+        if (x + y).is_multiple_of(2) {
+            &["left", "right", "bottom"]
+        } else {
+            &["left", "right", "top"]
+        }
     }
 
     fn init_neighbors(width: usize, height: usize, cells: &mut [MazeCell]) {
@@ -40,152 +49,128 @@ impl Shape for TriShape {
         }
     }
 
-    fn to_svg(maze: &GenericMaze<Self>, tunnel_width: usize, solution_path: Option<&[usize]>, debug: bool) -> String {
-        let tri_height = (tunnel_width as f64 * 0.866).round() as usize;
-        let svg_width = maze.width * tunnel_width / 2 + tunnel_width / 2;
-        let svg_height = maze.height * tri_height + tri_height;
-
-        let mut svg = String::new();
-        svg.push_str(&format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
-  <rect width="{}" height="{}" fill="white"/>
-  <g stroke="black" stroke-width="2" stroke-linecap="square" fill="none">
-"#,
-            svg_width, svg_height, svg_width, svg_height, svg_width, svg_height
-        ));
-
-        for y in 0..maze.height {
-            for x in 0..maze.width {
-                let idx = maze.cell_index(x, y);
-                let is_up = (x + y) % 2 == 0;
-
-                let base_x = x * tunnel_width / 2;
-                let base_y = y * tri_height;
+    fn drawing_size(maze: &GenericMaze<Self>, tunnel_width: usize) -> (f64, f64) {
+        let side = tunnel_width as f64;
+        let tri_height = (side * 0.866).round();
+        (maze.width as f64 * side / 2.0 + side / 2.0, maze.height as f64 * tri_height + tri_height)
+    }
 
-                if is_up {
-                    // Up-pointing triangle: vertices at bottom-left, top, bottom-right
-                    let x1 = base_x;                      // bottom-left
-                    let y1 = base_y + tri_height;
-                    let x2 = base_x + tunnel_width / 2;  // top
-                    let y2 = base_y;
-                    let x3 = base_x + tunnel_width;      // bottom-right
-                    let y3 = base_y + tri_height;
-
-                    // Draw walls (0=left edge, 1=right edge, 2=bottom edge)
-                    // Skip entrance (left edge of cell 0)
-                    if maze.cells[idx].walls[0] && !(idx == 0) {
-                        svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", x1, y1, x2, y2));
-                    }
-                    // Skip exit (right edge of last cell)
-                    if maze.cells[idx].walls[1] && !(idx == maze.cells.len() - 1) {
-                        svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", x2, y2, x3, y3));
-                    }
-                    if maze.cells[idx].walls[2] {
-                        svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", x1, y1, x3, y3));
-                    }
-                } else {
-                    // Down-pointing triangle: vertices at top-left, bottom, top-right
-                    let x1 = base_x;                      // top-left
-                    let y1 = base_y;
-                    let x2 = base_x + tunnel_width / 2;  // bottom
-                    let y2 = base_y + tri_height;
-                    let x3 = base_x + tunnel_width;      // top-right
-                    let y3 = base_y;
-
-                    // Draw walls (0=left edge, 1=right edge, 2=top edge)
-                    if maze.cells[idx].walls[0] {
-                        svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", x1, y1, x2, y2));
-                    }
-                    // Skip exit (right edge of last cell)
-                    if maze.cells[idx].walls[1] && !(idx == maze.cells.len() - 1) {
-                        svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", x2, y2, x3, y3));
-                    }
-                    if maze.cells[idx].walls[2] {
-                        svg.push_str(&format!("    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", x1, y1, x3, y3));
-                    }
-                }
-            }
+    fn cell_polygon(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Vec<Point> {
+        let tunnel_width = tunnel_width as f64;
+        let tri_height = (tunnel_width * 0.866).round();
+        let (x, y) = maze.cell_coords(idx);
+        let base_x = x as f64 * tunnel_width / 2.0;
+        let base_y = y as f64 * tri_height;
+
+        // Up-pointing triangle: vertices at bottom-left, top, bottom-right
+        // Down-pointing triangle: vertices at top-left, bottom, top-right
+        if (x + y).is_multiple_of(2) {
+            vec![
+                (base_x, base_y + tri_height),
+                (base_x + tunnel_width / 2.0, base_y),
+                (base_x + tunnel_width, base_y + tri_height),
+            ]
+        } else {
+            vec![
+                (base_x, base_y),
+                (base_x + tunnel_width / 2.0, base_y + tri_height),
+                (base_x + tunnel_width, base_y),
+            ]
         }
+    }
 
-        svg.push_str("  </g>\n");
-
-        // Add cell index labels for debugging
-        if debug {
-            svg.push_str("  <g font-family=\"monospace\" font-size=\"10\" text-anchor=\"middle\" fill=\"blue\">\n");
-            for y in 0..maze.height {
-                for x in 0..maze.width {
-                    let idx = maze.cell_index(x, y);
-                    let is_up = (x + y) % 2 == 0;
-                    let base_x = x * tunnel_width / 2;
-                    let base_y = y * tri_height;
-                    let (center_x, center_y) = if is_up {
-                        (base_x + tunnel_width / 2, base_y + tri_height * 2 / 3)
-                    } else {
-                        (base_x + tunnel_width / 2, base_y + tri_height / 3)
-                    };
-                    svg.push_str(&format!("    <text x=\"{}\" y=\"{}\">{}</text>\n", center_x, center_y + 3, idx));
-                }
-            }
-            svg.push_str("  </g>\n");
+    fn cell_center(maze: &GenericMaze<Self>, tunnel_width: usize, idx: usize) -> Point {
+        let side = tunnel_width as f64;
+        let tri_height = (side * 0.866).round();
+        let (x, y) = maze.cell_coords(idx);
+        let base_x = x as f64 * side / 2.0;
+        let base_y = y as f64 * tri_height;
+        if (x + y).is_multiple_of(2) {
+            (base_x + side / 2.0, base_y + tri_height * 2.0 / 3.0)
+        } else {
+            (base_x + side / 2.0, base_y + tri_height / 3.0)
         }
+    }
 
-        if let Some(path) = solution_path {
-            if !path.is_empty() {
-                svg.push_str("  <g stroke=\"red\" stroke-width=\"3\" stroke-linecap=\"round\" fill=\"none\">\n");
-                svg.push_str("    <path class=\"solution-path\" d=\"");
-
-                for (i, &idx) in path.iter().enumerate() {
-                    let (x, y) = maze.cell_coords(idx);
-                    let is_up = (x + y) % 2 == 0;
-
-                    let base_x = x * tunnel_width / 2;
-                    let base_y = y * tri_height;
-
-                    let (center_x, center_y) = if is_up {
-                        (base_x + tunnel_width / 2, base_y + tri_height * 2 / 3)
-                    } else {
-                        (base_x + tunnel_width / 2, base_y + tri_height / 3)
-                    };
-
-                    if i == 0 {
-                        svg.push_str(&format!("M {} {} ", center_x, center_y));
-                    } else {
-                        svg.push_str(&format!("L {} {} ", center_x, center_y));
-                    }
-                }
-
-                svg.push_str("\"/>\n");
-                svg.push_str("  </g>\n");
-            }
-        }
+    fn wall_corners(_maze: &GenericMaze<Self>, _idx: usize, wall_idx: usize) -> Option<(usize, usize)> {
+        // Walls: 0=left edge, 1=right edge, 2=bottom (up) or top (down) edge
+        [(0, 1), (1, 2), (0, 2)].get(wall_idx).copied()
+    }
 
-        svg.push_str("</svg>");
-        svg
+    fn label_size() -> f64 {
+        10.0
     }
 
-    fn print_debug_info(maze: &GenericMaze<Self>) {
-        println!("\n=== Triangular Maze Debug Info ===");
-        println!("Grid: {}x{} (width x height)", maze.width, maze.height);
-        println!("Total cells: {}", maze.cells.len());
-        println!("\nNeighbor relationships (indices: 0=left, 1=right, 2=top/bottom):");
+    fn to_text(maze: &GenericMaze<Self>, solution_path: Option<&[usize]>, style: TextStyle) -> String {
+        // Each triangle is three rows tall and six columns wide at its base,
+        // overlapping its left and right neighbors by half:
+        //   /\‾‾‾‾/\
+        //  /  \  /  \
+        // /____\/____\
+        // A down triangle's top edge is the underscore row of the row above.
+        let (rising, falling) = style.diagonals();
+        let mut grid = CharGrid::new(maze.width * 3 + 3, maze.height * 3 + 1);
+        let top_left = |x: usize, y: usize| (x * 3, y * 3 + 1);
 
         for y in 0..maze.height {
             for x in 0..maze.width {
                 let idx = maze.cell_index(x, y);
                 let is_up = (x + y) % 2 == 0;
-                print!("Cell {:2} (x={}, y={}, {}): [",
-                    idx, x, y, if is_up { "up  " } else { "down" });
-
-                for (i, &neighbor) in maze.cells[idx].neighbors.iter().enumerate() {
-                    if i > 0 { print!(", "); }
-                    match neighbor {
-                        Some(n) => print!("{:2}", n),
-                        None => print!("--"),
+                let (column, row) = top_left(x, y);
+
+                // Character cells of each edge: 0=left, 1=right, 2=bottom (up) or top (down)
+                let edges: [[(usize, usize, char); 3]; 3] = if is_up {
+                    [
+                        [(column + 2, row, rising), (column + 1, row + 1, rising), (column, row + 2, rising)],
+                        [(column + 3, row, falling), (column + 4, row + 1, falling), (column + 5, row + 2, falling)],
+                        [(column + 2, row + 2, '_'), (column + 3, row + 2, '_'), (column + 1, row + 2, '_')],
+                    ]
+                } else {
+                    [
+                        [(column, row, falling), (column + 1, row + 1, falling), (column + 2, row + 2, falling)],
+                        [(column + 5, row, rising), (column + 4, row + 1, rising), (column + 3, row + 2, rising)],
+                        [(column + 2, row - 1, '_'), (column + 3, row - 1, '_'), (column + 1, row - 1, '_')],
+                    ]
+                };
+                for (wall_idx, chars) in edges.iter().enumerate() {
+                    if maze.cells[idx].walls[wall_idx] && !maze.is_opening(idx, wall_idx) {
+                        for &(c, r, ch) in chars {
+                            grid.set(c, r, ch);
+                        }
                     }
                 }
-                println!("]");
+                // The base spans four columns; the outer two go with any wall on it
+                if maze.cells[idx].walls[2] && !maze.is_opening(idx, 2) {
+                    let base_row = if is_up { row + 2 } else { row - 1 };
+                    grid.set(column + 4, base_row, '_');
+                }
+            }
+        }
+
+        // Fill the solution cells and the open edges between consecutive ones
+        if let Some(path) = solution_path {
+            let mark = style.solution_mark();
+            for &idx in path {
+                let (x, y) = maze.cell_coords(idx);
+                let (column, row) = top_left(x, y);
+                grid.set(column + 2, row + 1, mark);
+                grid.set(column + 3, row + 1, mark);
+            }
+            for pair in path.windows(2) {
+                let ((x1, y1), (x2, y2)) = (maze.cell_coords(pair[0]), maze.cell_coords(pair[1]));
+                if y1 == y2 {
+                    // Middle character of the shared diagonal
+                    let (column, row) = top_left(x1.max(x2), y1);
+                    grid.set(column + 1, row + 1, mark);
+                } else {
+                    // Middle of the shared base
+                    let (column, row) = top_left(x1, y1.max(y2));
+                    grid.set(column + 2, row - 1, mark);
+                    grid.set(column + 3, row - 1, mark);
+                }
             }
         }
+
+        grid.finish()
     }
 }
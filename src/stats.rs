@@ -0,0 +1,246 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::genericmaze::{GenericMaze, Shape};
+
+/// Structural and difficulty metrics for a maze, comparable across grid types
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MazeStats {
+    /// Total number of cells
+    pub cells: usize,
+    /// Number of open passages between cells
+    pub passages: usize,
+    /// Cells with exactly one open passage
+    pub dead_ends: usize,
+    /// Number of cells with each passage count of 3 or more (3-way, 4-way, ...)
+    pub junctions: BTreeMap<usize, usize>,
+    /// Mean number of passages between junctions/dead ends (the "river" factor:
+    /// high for long winding corridors, low for bushy mazes)
+    pub mean_corridor_length: f64,
+    /// Cells on the solution path, including entrance and exit. This and
+    /// the other solution metrics are `None` when the exit can't be reached.
+    pub solution_length: Option<usize>,
+    /// Solution length as a fraction of all cells
+    pub solution_ratio: Option<f64>,
+    /// Cells on the solution path offering more than one way forward
+    pub decision_points: Option<usize>,
+    /// Changes of direction (edge label) along the solution path
+    pub turns: Option<usize>,
+}
+
+impl MazeStats {
+    /// Compute the statistics for a maze and its solution path. A path that
+    /// doesn't lead from the entrance to the exit through open passages, such
+    /// as the lone exit `solve` returns when the exit can't be reached,
+    /// leaves the solution metrics empty.
+    pub fn compute<S: Shape>(maze: &GenericMaze<S>, solution: &[usize]) -> Self {
+        let cells = maze.cells.len();
+        let degrees: Vec<usize> = (0..cells).map(|idx| maze.passages_of(idx).count()).collect();
+        let passages = degrees.iter().sum::<usize>() / 2;
+
+        let mut junctions = BTreeMap::new();
+        for &degree in degrees.iter().filter(|&&d| d >= 3) {
+            *junctions.entry(degree).or_insert(0) += 1;
+        }
+
+        // Corridors run between cells that are not simple pass-throughs; each
+        // one is counted once from each of its two ends
+        let corridor_ends: usize = degrees.iter().filter(|&&d| d != 2).sum();
+        let corridors = corridor_ends / 2;
+        let mean_corridor_length = if corridors > 0 {
+            passages as f64 / corridors as f64
+        } else {
+            passages as f64
+        };
+
+        // The edge label of each step, if every step is through an open passage
+        let moves: Option<Vec<&str>> = solution
+            .windows(2)
+            .map(|pair| {
+                let (edge_idx, _) = maze.passages_of(pair[0]).find(|&(_, n)| n == pair[1])?;
+                Some(maze.edge_labels(pair[0])[edge_idx])
+            })
+            .collect();
+        let solved = solution.first() == Some(&maze.entrance) && solution.last() == Some(&maze.exit);
+        let moves = moves.filter(|_| solved);
+
+        let decision_points = moves.as_ref().map(|_| {
+            let mut decision_points = 0;
+            for (i, &idx) in solution.iter().enumerate() {
+                if idx == maze.exit {
+                    break;
+                }
+                // Ways onward from here, not counting the way we came in
+                let onward = if i == 0 { degrees[idx] } else { degrees[idx] - 1 };
+                if onward >= 2 {
+                    decision_points += 1;
+                }
+            }
+            decision_points
+        });
+
+        MazeStats {
+            cells,
+            passages,
+            dead_ends: degrees.iter().filter(|&&d| d == 1).count(),
+            junctions,
+            mean_corridor_length,
+            solution_length: moves.as_ref().map(|_| solution.len()),
+            solution_ratio: moves.as_ref().map(|_| if cells > 0 { solution.len() as f64 / cells as f64 } else { 0.0 }),
+            decision_points,
+            turns: moves.map(|moves| moves.windows(2).filter(|pair| pair[0] != pair[1]).count()),
+        }
+    }
+
+    /// Human-readable report
+    pub fn to_text(&self) -> String {
+        let junctions = if self.junctions.is_empty() {
+            "none".to_string()
+        } else {
+            self.junctions
+                .iter()
+                .map(|(degree, count)| format!("{}-way: {}", degree, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let solution = match (self.solution_length, self.solution_ratio, self.decision_points, self.turns) {
+            (Some(length), Some(ratio), Some(decision_points), Some(turns)) => format!(
+                "Solution length:      {} cells ({:.1}% of cells)\n\
+                 Decision points:      {}\n\
+                 Turns:                {}\n",
+                length,
+                100.0 * ratio,
+                decision_points,
+                turns,
+            ),
+            _ => "Solution:             none, the exit can't be reached from the entrance\n".to_string(),
+        };
+
+        format!(
+            "Cells:                {}\n\
+             Passages:             {}\n\
+             Dead ends:            {} ({:.1}% of cells)\n\
+             Junctions:            {}\n\
+             Mean corridor length: {:.2}\n\
+             {}",
+            self.cells,
+            self.passages,
+            self.dead_ends,
+            100.0 * self.dead_ends as f64 / self.cells.max(1) as f64,
+            junctions,
+            self.mean_corridor_length,
+            solution,
+        )
+    }
+
+    /// JSON report
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("stats are always serializable")
+    }
+}
+
+/// Bounds a generated maze must satisfy; `None` leaves a bound open
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Constraints {
+    pub min_solution_ratio: Option<f64>,
+    pub max_solution_ratio: Option<f64>,
+    pub min_dead_ends: Option<usize>,
+    pub max_dead_ends: Option<usize>,
+}
+
+impl Constraints {
+    /// True if no bound is set
+    pub fn is_empty(&self) -> bool {
+        *self == Constraints::default()
+    }
+
+    /// Check that some maze could meet the bounds: no minimum above its maximum
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(min), Some(max)) = (self.min_solution_ratio, self.max_solution_ratio)
+            && min > max
+        {
+            return Err(format!("minimum solution ratio {} is above the maximum {}", min, max));
+        }
+        if let (Some(min), Some(max)) = (self.min_dead_ends, self.max_dead_ends)
+            && min > max
+        {
+            return Err(format!("minimum dead ends {} is above the maximum {}", min, max));
+        }
+        Ok(())
+    }
+
+    /// True if the stats fall within every bound. A maze whose exit can't be
+    /// reached meets no constraints.
+    pub fn satisfied_by(&self, stats: &MazeStats) -> bool {
+        let Some(solution_ratio) = stats.solution_ratio else {
+            return false;
+        };
+        self.min_solution_ratio.is_none_or(|min| solution_ratio >= min)
+            && self.max_solution_ratio.is_none_or(|max| solution_ratio <= max)
+            && self.min_dead_ends.is_none_or(|min| stats.dead_ends >= min)
+            && self.max_dead_ends.is_none_or(|max| stats.dead_ends <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::RectShape;
+
+    #[test]
+    fn test_stats_on_known_maze() {
+        // 3x2 rectangle carved as a snake: 0 -> 1 -> 2 -> 5 -> 4 -> 3
+        //   +--+--+--+
+        //   |0  1  2 |
+        //   +--+--+  +
+        //   |3  4  5 |
+        //   +--+--+--+
+        let mut maze = GenericMaze::<RectShape>::new(3, 2);
+        maze.carve(0, 2);
+        maze.carve(1, 2);
+        maze.carve(2, 1);
+        maze.carve(5, 3);
+        maze.carve(4, 3);
+        maze.exit = 3;
+
+        let stats = MazeStats::compute(&maze, &maze.solve());
+        assert_eq!(stats.cells, 6);
+        assert_eq!(stats.passages, 5);
+        assert_eq!(stats.dead_ends, 2);
+        assert!(stats.junctions.is_empty());
+        assert_eq!(stats.mean_corridor_length, 5.0);
+        assert_eq!(stats.solution_length, Some(6));
+        assert_eq!(stats.decision_points, Some(0));
+        assert_eq!(stats.turns, Some(2));
+
+        // An H shape: two 3-way junctions, both on the solution path 0 -> 1 -> 4 -> 5
+        let mut maze = GenericMaze::<RectShape>::new(3, 2);
+        maze.carve(0, 2);
+        maze.carve(1, 2);
+        maze.carve(1, 1);
+        maze.carve(4, 3);
+        maze.carve(4, 2);
+        let stats = MazeStats::compute(&maze, &maze.solve());
+        assert_eq!(stats.junctions.get(&3), Some(&2));
+        assert_eq!(stats.dead_ends, 4);
+        assert_eq!(stats.mean_corridor_length, 1.0);
+        assert_eq!(stats.decision_points, Some(2));
+        assert_eq!(stats.solution_length, Some(4));
+    }
+
+    #[test]
+    fn test_stats_without_a_solution() {
+        // Walled off, the exit can't be reached: `solve` gives just the exit
+        let maze = GenericMaze::<RectShape>::new(3, 2);
+        let stats = MazeStats::compute(&maze, &maze.solve());
+        assert_eq!((stats.solution_length, stats.solution_ratio, stats.decision_points, stats.turns), (None, None, None, None));
+        assert!(stats.to_text().contains("the exit can't be reached"));
+        assert!(stats.to_json().contains("\"solution_length\": null"));
+        assert!(!Constraints::default().satisfied_by(&stats));
+
+        // Nor is a path through walls a solution
+        let stats = MazeStats::compute(&maze, &[0, 1, 2, 5]);
+        assert_eq!(stats.solution_length, None);
+    }
+}
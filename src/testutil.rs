@@ -0,0 +1,49 @@
+// Helpers shared by the unit tests of several modules
+use crate::generate::{generate, GenerateOptions};
+use crate::genericmaze::{GenericMaze, Shape};
+
+/// Options for a `width` x `height` maze of shape `S`, generated from `seed`
+pub(crate) fn options<S: Shape>(width: usize, height: usize, seed: u64) -> GenerateOptions {
+    GenerateOptions { seed: Some(seed), ..GenerateOptions::new(S::name(), width, height) }
+}
+
+/// `generate` for a shape picked at compile time
+pub(crate) fn build<S: Shape>(options: &GenerateOptions) -> GenericMaze<S> {
+    let (maze, _) = generate(options).expect("options describe a valid maze");
+    maze.into_generic().expect("maze has the requested shape")
+}
+
+/// Run each generic test `name::<S>()` as its own `#[test]` for every shape,
+/// in a module named after the test, so every shape passes or fails
+/// separately and a failure names the shape
+macro_rules! shape_tests {
+    ($($test:ident),+ $(,)?) => {$(
+        mod $test {
+            #[test]
+            fn rectangular() {
+                super::$test::<$crate::shapes::RectShape>();
+            }
+
+            #[test]
+            fn triangular() {
+                super::$test::<$crate::shapes::TriShape>();
+            }
+
+            #[test]
+            fn hexagonal() {
+                super::$test::<$crate::shapes::HexShape>();
+            }
+
+            #[test]
+            fn octagonal() {
+                super::$test::<$crate::shapes::OctShape>();
+            }
+
+            #[test]
+            fn polar() {
+                super::$test::<$crate::shapes::PolarShape>();
+            }
+        }
+    )+};
+}
+pub(crate) use shape_tests;
@@ -0,0 +1,313 @@
+use std::io;
+
+use crate::mazefile::{MazeFile, FORMAT_VERSION};
+use crate::render::junction_arms;
+use crate::shapes::RectShape;
+
+// Importer for rectangular mazes drawn as text, so hand-sketched mazes can be
+// rendered and solved like generated ones. Two layouts are accepted:
+//
+// ```text
+// Line drawings, as written by the       Blocks: one character per cell
+// text renderer (any cell width):        and per wall, walls are '#'
+//
+// +  +--+--+      ╷  ╶─────┐              # #######
+// |        |      │        │              #       #
+// +--+--+  +      ├─────╴  │              ##### # #
+// |        |      │        │              #       #
+// +  +--+--+      ╵  ╶─────┘              # #######
+// ```
+//
+// Any input containing '#' is read as blocks. Solution marks ('*', '░') are
+// treated as open space, and anything after the first blank line (such as
+// the solution printed by `-o -`) is ignored. Gaps in the outline become the
+// entrance and exit, in reading order, opening on the side they were drawn
+// on; with no gaps the defaults are kept.
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Text split into rows of characters, padded with spaces on demand
+struct TextGrid {
+    rows: Vec<Vec<char>>,
+}
+
+impl TextGrid {
+    fn new(text: &str) -> Self {
+        let rows = text
+            .lines()
+            .map(|line| line.trim_end())
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| !line.is_empty())
+            .map(|line| line.chars().collect())
+            .collect();
+        TextGrid { rows }
+    }
+
+    fn get(&self, column: usize, row: usize) -> char {
+        self.rows.get(row).and_then(|r| r.get(column)).copied().unwrap_or(' ')
+    }
+
+    fn columns(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+/// How walls are drawn: blocks or lines, and the characters per cell column
+#[derive(Clone, Copy, PartialEq)]
+enum Layout {
+    Blocks,
+    Lines { stride: usize },
+}
+
+impl Layout {
+    fn stride(self) -> usize {
+        match self {
+            Layout::Blocks => 2,
+            Layout::Lines { stride } => stride,
+        }
+    }
+
+    /// Whether the character at a wall position draws a wall
+    fn is_wall(self, c: char, horizontal: bool, column: usize, row: usize) -> io::Result<bool> {
+        let wall = match (self, c) {
+            (Layout::Blocks, c) => c == '#',
+            (Layout::Lines { .. }, ' ' | '*' | '░') => false,
+            (Layout::Lines { .. }, '-' | '─' | '_') if horizontal => true,
+            (Layout::Lines { .. }, '|' | '│') if !horizontal => true,
+            _ => {
+                return Err(invalid(format!(
+                    "unexpected '{}' at line {}, column {}",
+                    c,
+                    row + 1,
+                    column + 1
+                )));
+            }
+        };
+        Ok(wall)
+    }
+}
+
+/// Work out the cell width of a line drawing from where its corners sit
+fn line_stride(grid: &TextGrid) -> io::Result<usize> {
+    let is_corner = |c: char| c == '+' || (c != '─' && junction_arms(c).is_some());
+    let stride = grid
+        .rows
+        .iter()
+        .step_by(2)
+        .flat_map(|row| row.iter().enumerate().filter(|&(_, &c)| is_corner(c)).map(|(column, _)| column))
+        .filter(|&column| column > 0)
+        .fold(0, gcd);
+    match stride {
+        0 => Ok(3),
+        1 => Err(invalid("corners must be at least two columns apart")),
+        stride => Ok(stride),
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Parse a rectangular maze drawn as text. The result is validated like a
+/// saved maze when converted with `MazeFile::to_maze`.
+pub fn parse(text: &str) -> io::Result<MazeFile> {
+    let grid = TextGrid::new(text);
+    if grid.rows.is_empty() {
+        return Err(invalid("no maze found in the text"));
+    }
+    let layout = if grid.rows.iter().flatten().any(|&c| c == '#') {
+        Layout::Blocks
+    } else {
+        Layout::Lines { stride: line_stride(&grid)? }
+    };
+    let stride = layout.stride();
+
+    let (columns, rows) = (grid.columns(), grid.rows.len());
+    if rows < 3 || rows % 2 == 0 || columns <= stride || (columns - 1) % stride != 0 {
+        return Err(invalid(format!(
+            "a {} by {} character drawing does not fit a grid of cells {} characters wide",
+            columns, rows, stride
+        )));
+    }
+    let (width, height) = ((columns - 1) / stride, (rows - 1) / 2);
+
+    // A horizontal wall spans the columns between two corners; all of them must agree
+    let horizontal_wall = |x: usize, row: usize| -> io::Result<bool> {
+        let mut drawn = Vec::with_capacity(stride - 1);
+        for column in x * stride + 1..(x + 1) * stride {
+            drawn.push(layout.is_wall(grid.get(column, row), true, column, row)?);
+        }
+        if drawn.iter().any(|&d| d != drawn[0]) {
+            return Err(invalid(format!("partly drawn wall at line {}, column {}", row + 1, x * stride + 2)));
+        }
+        Ok(drawn[0])
+    };
+    let vertical_wall = |column: usize, y: usize| layout.is_wall(grid.get(column, y * 2 + 1), false, column, y * 2 + 1);
+
+    // Walls in RectShape neighbor order: N, S, E, W
+    let mut walls = Vec::with_capacity(width * height);
+    let mut gaps = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let idx = y * width + x;
+            let cell = [
+                horizontal_wall(x, y * 2)?,
+                horizontal_wall(x, y * 2 + 2)?,
+                vertical_wall((x + 1) * stride, y)?,
+                vertical_wall(x * stride, y)?,
+            ];
+            let on_outline = [y == 0, y == height - 1, x == width - 1, x == 0];
+            for wall_idx in 0..4 {
+                if on_outline[wall_idx] && !cell[wall_idx] {
+                    gaps.push((idx, wall_idx));
+                }
+            }
+            // The outline is always closed in the maze itself; gaps become the endpoints
+            walls.push((0..4).map(|wall_idx| cell[wall_idx] || on_outline[wall_idx]).collect());
+
+            if layout == Layout::Blocks && grid.get(x * 2 + 1, y * 2 + 1) == '#' {
+                return Err(invalid(format!("cell at line {}, column {} is filled in", y * 2 + 2, x * 2 + 2)));
+            }
+        }
+    }
+
+    if let Layout::Lines { .. } = layout {
+        check_corners(&grid, stride, width, height)?;
+    }
+
+    // Each gap keeps the side it was drawn on, so corner cells reopen the same wall
+    let ((entrance, entrance_wall), (exit, exit_wall)) = match gaps[..] {
+        [] => ((0, None), (width * height - 1, None)),
+        [(entrance, entrance_wall), (exit, exit_wall)] if entrance != exit || width * height == 1 => {
+            ((entrance, Some(entrance_wall)), (exit, Some(exit_wall)))
+        }
+        [(idx, _), _] => {
+            return Err(invalid(format!(
+                "the corner cell at line {}, column {} has gaps on both sides; the entrance and exit must be in different cells",
+                idx / width * 2 + 2,
+                idx % width * stride + 2
+            )))
+        }
+        _ => return Err(invalid(format!("expected two gaps in the outline (entrance and exit), found {}", gaps.len()))),
+    };
+
+    let file = MazeFile {
+        version: FORMAT_VERSION,
+        grid_type: "rectangular".to_string(),
+        width,
+        height,
+        seed: None,
+        entrance,
+        exit,
+        entrance_wall,
+        exit_wall,
+        walls,
+        solution: None,
+    };
+
+    let maze = file.to_maze::<RectShape>()?;
+    if maze.distances_from(entrance)[exit].is_none() {
+        return Err(invalid("there is no path from the entrance to the exit"));
+    }
+    Ok(file)
+}
+
+/// Box-drawing corners say which walls meet there; check that they agree with
+/// the walls actually drawn around them
+fn check_corners(grid: &TextGrid, stride: usize, width: usize, height: usize) -> io::Result<()> {
+    let is_wall = |c: char| matches!(c, '-' | '─' | '_' | '|' | '│');
+    for row in (0..=height * 2).step_by(2) {
+        for column in (0..=width * stride).step_by(stride) {
+            let Some(arms) = junction_arms(grid.get(column, row)) else { continue };
+            let drawn = (
+                row > 0 && is_wall(grid.get(column, row - 1)),
+                is_wall(grid.get(column, row + 1)),
+                column > 0 && is_wall(grid.get(column - 1, row)),
+                is_wall(grid.get(column + 1, row)),
+            );
+            if arms != drawn {
+                return Err(invalid(format!(
+                    "corner '{}' at line {}, column {} does not match the walls around it",
+                    grid.get(column, row),
+                    row + 1,
+                    column + 1
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoints::{place_endpoints, Endpoint, Side};
+    use crate::genericmaze::Shape;
+    use crate::mazefile::{load_saved, saved_grid_type};
+    use crate::packed::to_packed;
+    use crate::render::TextStyle;
+    use crate::testutil::{build, options};
+
+    #[test]
+    fn test_text_import() {
+        // Generated drawings in either style read back as the same maze
+        let maze = build::<RectShape>(&options::<RectShape>(9, 7, 5));
+        let solution = maze.solve();
+        for style in [TextStyle::Ascii, TextStyle::Unicode] {
+            let text = RectShape::to_text(&maze, Some(&solution), style);
+            assert_eq!(saved_grid_type(text.as_bytes()).unwrap(), "rectangular");
+            let (loaded, _) = load_saved::<RectShape>(text.as_bytes()).unwrap();
+            assert_eq!((loaded.width, loaded.height, loaded.entrance, loaded.exit), (9, 7, maze.entrance, maze.exit));
+            assert!((0..maze.cells.len()).all(|idx| maze.cells[idx].walls == loaded.cells[idx].walls));
+        }
+
+        // Block drawings and wider line drawings, with gaps anywhere on the outline
+        let blocks = "#####\n\
+                      #   #\n\
+                      # # #\n\
+                      \x20 #  \n\
+                      #####\n";
+        let file = parse(blocks).unwrap();
+        assert_eq!((file.width, file.height, file.entrance, file.exit), (2, 2, 2, 3));
+        assert_eq!((file.entrance_wall, file.exit_wall), (Some(3), Some(2)));
+        let wide = "+   +---+\n\
+                    |       |\n\
+                    +---+   +\n\
+                    |        \n\
+                    +---+---+\n";
+        let file = parse(wide).unwrap();
+        assert_eq!((file.width, file.height, file.entrance, file.exit), (2, 2, 0, 3));
+        assert_eq!(file.walls[0], vec![true, true, false, true]);
+
+        // Corner gaps reopen on the side they were drawn on, in every format
+        let maze = file.to_maze::<RectShape>().unwrap();
+        let redrawn = RectShape::to_text(&maze, None, TextStyle::Ascii);
+        assert_eq!(parse(&redrawn).unwrap().exit_wall, Some(2));
+        assert_eq!(redrawn.lines().nth(3), Some("|"));
+        let json = MazeFile::from_json(&file.to_json()).unwrap();
+        assert_eq!(json.to_maze::<RectShape>().unwrap().exit_wall, Some(2));
+        assert!(to_packed(&maze).is_err());
+        let mut moved = maze;
+        place_endpoints(&mut moved, None, Some(Endpoint::Side(Side::South))).unwrap();
+        assert!(to_packed(&moved).is_ok());
+
+        // A drawing without gaps keeps the default corners
+        let closed = "+--+--+\n|     |\n+--+--+\n";
+        assert_eq!((parse(closed).unwrap().entrance, parse(closed).unwrap().exit), (0, 1));
+
+        let bad = [
+            "+  +--+\n|     |\n+--+--+\n",       // only one gap
+            "+  +--+\n|  |  |\n+--+  +\n",       // exit cut off
+            "+  +--+\n|  x  |\n+--+  +\n",       // stray character
+            "+  +--+\n|     |\n+--+- +\n",       // half a wall
+            "╷  ╶──┐\n│     │\n└──┘  ╵\n",       // corner drawn without its wall
+            "# ###\n#  ##\n### #\n",             // filled-in cell
+            "   +--+\n   |  |\n+--+--+\n",       // both gaps on one corner
+        ];
+        for text in bad {
+            assert!(parse(text).is_err(), "accepted {:?}", text);
+        }
+    }
+}